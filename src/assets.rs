@@ -0,0 +1,267 @@
+//! Files the game refers to by name, and where the engine finds them.
+//!
+//! A name has no extension: the engine looks for it in the game folder first and in the RTP
+//! after, trying every extension of the kind in order. Windows does not care about the case of
+//! file names, so neither does the search. Move routes are left out, since their graphic and
+//! sound changes are not decoded.
+
+use std::collections::{BTreeMap, HashMap};
+
+use lcf::raw::lmu::event::{command::Command, instruction::Instruction};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    CharSet,
+    ChipSet,
+    Music,
+    Panorama,
+    Picture,
+    Sound,
+}
+
+impl Kind {
+    pub const ALL: [Self; 6] = [
+        Self::CharSet,
+        Self::ChipSet,
+        Self::Music,
+        Self::Panorama,
+        Self::Picture,
+        Self::Sound,
+    ];
+
+    /// The folder the files are in, both in the game folder and in the RTP.
+    #[must_use]
+    pub const fn dir(self) -> &'static str {
+        match self {
+            Self::CharSet => "CharSet",
+            Self::ChipSet => "ChipSet",
+            Self::Music => "Music",
+            Self::Panorama => "Panorama",
+            Self::Picture => "Picture",
+            Self::Sound => "Sound",
+        }
+    }
+
+    /// The extensions the engine tries, in order.
+    #[must_use]
+    pub const fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::CharSet | Self::ChipSet | Self::Panorama | Self::Picture => {
+                &["png", "bmp", "xyz"]
+            }
+            Self::Music | Self::Sound => &["wav", "mp3", "ogg", "mid"],
+        }
+    }
+}
+
+/// A file referred to by name, still encoded in Shift-JIS.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Asset {
+    pub kind: Kind,
+    pub name: Vec<u8>,
+}
+
+impl Asset {
+    /// The name of the file the way the editor shows it, as in `Picture/title`.
+    #[must_use]
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}",
+            self.kind.dir(),
+            encoding_rs::SHIFT_JIS.decode(&self.name).0
+        )
+    }
+}
+
+/// Calls `found` with the index of the command for every file the commands refer to.
+pub fn commands(commands: &[Command], mut found: impl FnMut(usize, Asset)) {
+    for (index, command) in commands.iter().enumerate() {
+        let kind = match command.instruction {
+            Instruction::ShowPicture { .. } => Kind::Picture,
+            Instruction::PlayBGM { .. } | Instruction::ChangeSystemBGM { .. } => Kind::Music,
+            Instruction::PlaySE { .. } => Kind::Sound,
+            Instruction::ChangeParallaxBackground { .. } => Kind::Panorama,
+            Instruction::ChangeActorGraphic { .. } | Instruction::ChangeVehicleGraphic { .. } => {
+                Kind::CharSet
+            }
+            _ => continue,
+        };
+        if let Some(asset) = named(kind, &command.string) {
+            found(index, asset);
+        }
+    }
+}
+
+/// The file with the name, unless the name stands for no file at all.
+#[must_use]
+pub fn named(kind: Kind, name: &[u8]) -> Option<Asset> {
+    // the editor saves a blank name or `(OFF)` for playing nothing
+    if name.is_empty() || matches!(kind, Kind::Music | Kind::Sound) && name == b"(OFF)" {
+        return None;
+    }
+    Some(Asset {
+        kind,
+        name: name.to_vec(),
+    })
+}
+
+/// Every file a map refers to, along with the chipset it uses.
+#[must_use]
+pub fn of_map(map: &lcf::lmu::LcfMapUnit) -> Vec<Asset> {
+    let mut assets = Vec::new();
+    if map.panorama.enabled
+        && let Some(asset) = map
+            .panorama
+            .file
+            .as_deref()
+            .and_then(|file| named(Kind::Panorama, file))
+    {
+        assets.push(asset);
+    }
+    for page in map.events.iter().flat_map(|event| &event.pages) {
+        assets.extend(named(Kind::CharSet, &page.graphic.file));
+        commands(&page.commands, |_, asset| assets.push(asset));
+    }
+    assets.sort();
+    assets.dedup();
+    assets
+}
+
+/// Every file the database refers to on its own, without any map using it.
+#[must_use]
+pub fn of_database(database: &lcf::ldb::LcfDataBase) -> Vec<Asset> {
+    let system = &database.system;
+    let mut assets = Vec::new();
+    assets.extend(
+        database
+            .chipsets
+            .iter()
+            .filter_map(|chipset| named(Kind::ChipSet, &chipset.file)),
+    );
+    assets.extend(
+        database
+            .actors
+            .iter()
+            .filter_map(|actor| named(Kind::CharSet, &actor.charset_name)),
+    );
+    for common_event in &database.common_events {
+        commands(&common_event.commands, |_, asset| assets.push(asset));
+    }
+    assets.extend(
+        [
+            &system.boat_graphic_file,
+            &system.ship_graphic_file,
+            &system.airship_graphic_file,
+        ]
+        .into_iter()
+        .filter_map(|file| named(Kind::CharSet, file)),
+    );
+    assets.extend(
+        [
+            &system.title_screen_music,
+            &system.battle_music,
+            &system.battle_end_music,
+            &system.inn_music,
+            &system.boat_music,
+            &system.ship_music,
+            &system.airship_music,
+            &system.game_over_music,
+        ]
+        .into_iter()
+        .filter_map(|music| named(Kind::Music, &music.sound.file)),
+    );
+    assets.extend(
+        [
+            &system.cursor_sound_effect,
+            &system.select_sound_effect,
+            &system.cancel_sound_effect,
+            &system.buzzer_sound_effect,
+            &system.battle_start_sound_effect,
+            &system.escape_sound_effect,
+            &system.enemy_attack_sound_effect,
+            &system.enemy_damage_sound_effect,
+            &system.actor_damage_sound_effect,
+            &system.evasion_sound_effect,
+            &system.enemy_collapse_sound_effect,
+            &system.use_item_sound_effect,
+        ]
+        .into_iter()
+        .filter_map(|sound| named(Kind::Sound, &sound.file)),
+    );
+    assets.sort();
+    assets.dedup();
+    assets
+}
+
+/// The asset files of the game folder and the RTP, listed once for the whole run.
+pub struct Files {
+    /// For every kind, the files of each folder in search order, the game folder first. File names
+    /// are keyed by their lowercase form.
+    folders: HashMap<Kind, Vec<BTreeMap<String, String>>>,
+}
+
+impl Files {
+    #[must_use]
+    pub fn new(dir: &std::path::Path, rtp: &[std::path::PathBuf]) -> Self {
+        let folders = Kind::ALL
+            .into_iter()
+            .map(|kind| {
+                let listed = std::iter::once(dir)
+                    .chain(rtp.iter().map(std::path::PathBuf::as_path))
+                    .map(|root| list(root, kind.dir()))
+                    .collect();
+                (kind, listed)
+            })
+            .collect();
+        Self { folders }
+    }
+
+    /// Finds the file the engine would load for the asset, as its name on disk.
+    #[must_use]
+    pub fn find(&self, asset: &Asset) -> Option<&str> {
+        let name = encoding_rs::SHIFT_JIS.decode(&asset.name).0.to_lowercase();
+        let folders = self.folders.get(&asset.kind)?;
+        folders.iter().find_map(|files| {
+            asset.kind.extensions().iter().find_map(|extension| {
+                files
+                    .get(&format!("{name}.{extension}"))
+                    .map(String::as_str)
+            })
+        })
+    }
+
+    /// The files of a kind in the game folder, as their name on disk.
+    pub fn in_game(&self, kind: Kind) -> impl Iterator<Item = &str> {
+        self.folders
+            .get(&kind)
+            .and_then(|folders| folders.first())
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .map(String::as_str)
+    }
+}
+
+/// The files in the folder of a root, whatever the case of the folder's name. Missing folders are
+/// simply empty.
+fn list(root: &std::path::Path, folder: &str) -> BTreeMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return BTreeMap::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(folder)
+        })
+        .filter_map(|entry| std::fs::read_dir(entry.path()).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .map(|entry| {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            (file.to_lowercase(), file)
+        })
+        .collect()
+}
@@ -0,0 +1,264 @@
+//! Recording of known diagnostics so that only new ones are reported.
+//!
+//! Commands are identified by a fingerprint of their content rather than their index, since
+//! inserting a command shifts the index of everything after it.
+
+use std::collections::HashMap;
+
+use crate::{Diagnostic, Lint};
+
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Entry {
+    map: Option<u16>,
+    lint: String,
+    event: Option<u32>,
+    page: Option<u32>,
+    fingerprint: Option<String>,
+    message: Option<String>,
+}
+
+impl Entry {
+    #[must_use]
+    pub const fn map(&self) -> Option<u16> {
+        self.map
+    }
+
+    #[must_use]
+    pub fn new(
+        map_id: Option<u16>,
+        map: &lcf::lmu::LcfMapUnit,
+        lint: &dyn Lint,
+        diagnostic: &Diagnostic,
+    ) -> Self {
+        let event = diagnostic.event.as_ref();
+        let page = event.and_then(|event| event.page());
+        let command = event.zip(page).and_then(|(event, page)| {
+            map.events
+                .iter()
+                .find(|candidate| candidate.id == event.id())?
+                .pages
+                .get(page.index())?
+                .commands
+                .get(page.command_index()?)
+        });
+
+        Self {
+            map: map_id,
+            lint: lint.code().to_string(),
+            event: event.map(crate::DiagnosticEvent::id),
+            page: page.map(crate::DiagnosticPage::id),
+            fingerprint: command.map(fingerprint),
+            message: diagnostic.message.clone(),
+        }
+    }
+
+    /// An entry for a diagnostic of a project lint, which has no command to fingerprint.
+    #[must_use]
+    pub fn project(lint: &dyn Lint, diagnostic: &Diagnostic) -> Self {
+        let event = diagnostic.event.as_ref();
+        Self {
+            map: None,
+            lint: lint.code().to_string(),
+            event: event.map(crate::DiagnosticEvent::id),
+            page: event
+                .and_then(crate::DiagnosticEvent::page)
+                .map(crate::DiagnosticPage::id),
+            fingerprint: None,
+            message: diagnostic.message.clone(),
+        }
+    }
+}
+
+/// FNV-1a of the serialized instruction and its string, which is stable across runs and platforms.
+fn fingerprint(command: &lcf::raw::lmu::event::command::Command) -> String {
+    let mut bytes = serde_json::to_vec(&command.instruction).unwrap();
+    bytes.extend_from_slice(&command.string);
+
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct File {
+    version: u32,
+    entries: Vec<CountedEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CountedEntry {
+    #[serde(flatten)]
+    entry: Entry,
+    count: usize,
+}
+
+pub enum BaselineError {
+    Io(std::path::PathBuf, std::io::Error),
+    Parse(std::path::PathBuf, serde_json::Error),
+    Version(std::path::PathBuf, u32),
+}
+
+impl std::fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::Parse(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::Version(path, version) => write!(
+                f,
+                "{}: unsupported baseline version {version}, expected {VERSION}",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// How many times each diagnostic was recorded, grouped by map.
+#[derive(Default)]
+pub struct Baseline(HashMap<Option<u16>, HashMap<Entry, usize>>);
+
+impl Baseline {
+    pub fn load(path: &std::path::Path) -> Result<Self, BaselineError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| BaselineError::Io(path.to_owned(), err))?;
+        let file = serde_json::from_str::<File>(&text)
+            .map_err(|err| BaselineError::Parse(path.to_owned(), err))?;
+        if file.version != VERSION {
+            return Err(BaselineError::Version(path.to_owned(), file.version));
+        }
+
+        let mut baseline = Self::default();
+        for CountedEntry { entry, count } in file.entries {
+            *baseline
+                .0
+                .entry(entry.map)
+                .or_default()
+                .entry(entry)
+                .or_default() += count;
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(path: &std::path::Path, entries: Vec<Entry>) -> Result<(), BaselineError> {
+        let mut counts = indexmap::IndexMap::<Entry, usize>::new();
+        for entry in entries {
+            *counts.entry(entry).or_default() += 1;
+        }
+
+        let file = File {
+            version: VERSION,
+            entries: counts
+                .into_iter()
+                .map(|(entry, count)| CountedEntry { entry, count })
+                .collect(),
+        };
+
+        let text = serde_json::to_string_pretty(&file).unwrap();
+        std::fs::write(path, text).map_err(|err| BaselineError::Io(path.to_owned(), err))
+    }
+
+    /// The recorded entries of a single map, to be consumed as matching diagnostics are found.
+    #[must_use]
+    pub fn for_map(&self, map: Option<u16>) -> HashMap<Entry, usize> {
+        self.0.get(&map).cloned().unwrap_or_default()
+    }
+}
+
+/// Consumes one recorded occurrence of `entry`, returning whether there was one left.
+pub fn consume(remaining: &mut HashMap<Entry, usize>, entry: &Entry) -> bool {
+    match remaining.get_mut(entry) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lcf::{enums::Trigger, raw::lmu::event::instruction::Instruction};
+
+    use super::{Entry, consume};
+    use crate::lints::testing::{commands, map, page, project, test};
+
+    fn assign(value: u32) -> Instruction {
+        Instruction::ControlVariables {
+            mode: 0,
+            start: 44,
+            end: 44,
+            operation: 0,
+            operand: 0,
+            value1: value,
+            value2: 0,
+            unknown: None,
+        }
+    }
+
+    fn entries(instructions: Vec<Instruction>) -> Vec<Entry> {
+        let lint = crate::lints::ALL
+            .iter()
+            .find(|lint| lint.code() == "v44-assignment")
+            .unwrap();
+        let map = map(vec![vec![page(
+            Trigger::ActionButton,
+            commands(instructions),
+        )]]);
+        test(*lint, &map, &project(Vec::new()))
+            .iter()
+            .map(|diagnostic| Entry::new(Some(1), &map, *lint, diagnostic))
+            .collect()
+    }
+
+    fn recorded(entries: Vec<Entry>) -> std::collections::HashMap<Entry, usize> {
+        let mut counts = std::collections::HashMap::new();
+        for entry in entries {
+            *counts.entry(entry).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn command_inserted_above() {
+        let mut remaining = recorded(entries(vec![assign(1)]));
+        let found = entries(vec![
+            Instruction::Wait {
+                deciseconds: 10,
+                unknown: None,
+            },
+            assign(1),
+        ]);
+        assert_eq!(found.len(), 1);
+        assert!(consume(&mut remaining, &found[0]));
+    }
+
+    #[test]
+    fn duplicates_consumed_by_count() {
+        let mut remaining = recorded(entries(vec![assign(1), assign(1)]));
+        let found = entries(vec![assign(1), assign(1), assign(1)]);
+        assert_eq!(
+            found
+                .iter()
+                .map(|entry| consume(&mut remaining, entry))
+                .collect::<Vec<_>>(),
+            [true, true, false]
+        );
+    }
+
+    #[test]
+    fn changed_command() {
+        let mut remaining = recorded(entries(vec![assign(1)]));
+        let found = entries(vec![assign(2)]);
+        assert!(!consume(&mut remaining, &found[0]));
+    }
+
+    #[test]
+    fn changed_message() {
+        let mut remaining = recorded(entries(vec![assign(1)]));
+        let mut found = entries(vec![assign(1)]).remove(0);
+        found.message = Some(String::from("Something else"));
+        assert!(!consume(&mut remaining, &found));
+    }
+}
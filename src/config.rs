@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use crate::{DiagnosticLevel, FailOn, Lint, LogLevel, lints};
+
+/// Name of the configuration file looked up in the game folder, next to `RPG_RT.lmt`.
+pub const FILE_NAME: &str = ".lcf-validate.toml";
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub level: Option<LogLevel>,
+    pub fail_on: Option<FailOn>,
+    /// Lint code or number to its setting. Lints that are not listed run at their default level.
+    pub lints: HashMap<String, LintSetting>,
+    pub settings: Settings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSetting {
+    Off,
+    On,
+    Warning,
+    Error,
+}
+
+/// Values used by individual lints. Every field has a default matching 2kki's rules.
+#[derive(Debug, serde::Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// Maximum length of a comment, in characters. Each level of indentation takes 2 off of it.
+    pub comment_max_length: u32,
+    /// Comments containing any of these mark a blue sign as annotated.
+    pub blue_sign_exclusions: Vec<String>,
+    /// Comments containing any of these excuse transferring the player without unPADEing them first.
+    pub pade_transfer_exclusions: Vec<String>,
+    /// Variable that must be kept in sync with the weather.
+    pub weather_variable: u32,
+    /// Variable that maps must never assign to.
+    pub read_only_variable: u32,
+    /// Map every other map should be reachable from, the party start map if not set.
+    pub start_map: Option<u16>,
+    /// How many tiles away from the door it was entered through the way back may land.
+    pub door_tolerance: u32,
+    /// Whose ranges may be written to, usually the developer running the check.
+    pub owner: Option<String>,
+    /// Switch and variable ranges handed out to developers or set aside.
+    pub ownership: Vec<Range>,
+    /// RTP folders searched for assets the game folder does not have, in order.
+    pub rtp: Vec<std::path::PathBuf>,
+}
+
+/// A range of switches or variables, from `start` to `end` inclusive.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Range {
+    pub kind: crate::xref::Kind,
+    pub start: u32,
+    /// Same as `start` if not set, for ranges of a single id.
+    pub end: Option<u32>,
+    /// Only the owner may write to the range.
+    pub owner: Option<String>,
+    /// Nobody may write to the range, the owner included.
+    #[serde(default)]
+    pub read_only: bool,
+    /// The range is set aside and must not be used yet.
+    #[serde(default)]
+    pub reserved: bool,
+}
+
+impl Range {
+    #[must_use]
+    pub fn contains(&self, kind: crate::xref::Kind, id: u32) -> bool {
+        self.kind == kind && self.start <= id && id <= self.end.unwrap_or(self.start)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            comment_max_length: 56,
+            blue_sign_exclusions: ["eserved", "予約", "接続"].map(String::from).to_vec(),
+            pade_transfer_exclusions: vec![
+                "移動先マップで直接「ｲﾍﾞﾝﾄ中動作禁止解除」しています。".to_string(),
+            ],
+            weather_variable: 42,
+            read_only_variable: 44,
+            start_map: None,
+            door_tolerance: 3,
+            owner: None,
+            ownership: Vec::new(),
+            rtp: Vec::new(),
+        }
+    }
+}
+
+pub enum ConfigError {
+    Io(std::path::PathBuf, std::io::Error),
+    Parse(std::path::PathBuf, toml::de::Error),
+    UnknownLint(std::path::PathBuf, String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::Parse(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::UnknownLint(path, name) => {
+                write!(f, "{}: unknown lint `{name}`", path.display())
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration file at `path`, failing if it does not exist.
+    pub fn load(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_owned(), err))?;
+        let mut config = toml::from_str::<Self>(&text)
+            .map_err(|err| ConfigError::Parse(path.to_owned(), err))?;
+
+        // normalize the keys so that aliases and codes can be mixed
+        config.lints = config
+            .lints
+            .into_iter()
+            .map(|(name, setting)| {
+                let lint = lints::find(&name)
+                    .ok_or_else(|| ConfigError::UnknownLint(path.to_owned(), name))?;
+                Ok((lint.code().to_string(), setting))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(config)
+    }
+
+    /// Reads the configuration file in the game folder, or uses the defaults if there is none.
+    pub fn discover(dir: &std::path::Path) -> Result<Self, ConfigError> {
+        let path = dir.join(FILE_NAME);
+        if path.is_file() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self, lint: &dyn Lint) -> bool {
+        self.lints.get(lint.code()) != Some(&LintSetting::Off)
+    }
+
+    /// The level that overrides every diagnostic of the lint, if any.
+    #[must_use]
+    pub fn level(&self, lint: &dyn Lint) -> Option<DiagnosticLevel> {
+        match self.lints.get(lint.code())? {
+            LintSetting::Warning => Some(DiagnosticLevel::Warning),
+            LintSetting::Error => Some(DiagnosticLevel::Error),
+            LintSetting::Off | LintSetting::On => None,
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! The world as a directed graph of maps connected by player transfers.
+//!
+//! Common events are nodes of their own: maps point to the common events they call, and common
+//! events to the maps they transfer to and the common events they call in turn. Only transfers to a
+//! fixed destination are known, so anything moving the player through variables is missing.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use lcf::{enums::Trigger, raw::lmu::event::instruction::Instruction};
+
+use crate::project::Project;
+
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Node {
+    Map(u16),
+    CommonEvent(u32),
+}
+
+impl std::fmt::Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Map(id) => write!(f, "Map{id:04}"),
+            Self::CommonEvent(id) => write!(f, "CEV{id:04}"),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct NodeInfo {
+    #[serde(flatten)]
+    pub node: Node,
+    pub name: String,
+    /// Common events that start on their own, and can therefore be run from anywhere.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub automatic: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct Edge {
+    pub from: Node,
+    pub to: Node,
+    /// The map event doing the transfer or call, if it comes from a map.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<u32>,
+    /// Where the player lands, for transfers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Graph {
+    pub nodes: Vec<NodeInfo>,
+    pub edges: Vec<Edge>,
+    #[serde(skip)]
+    outgoing: HashMap<Node, Vec<Node>>,
+}
+
+impl Graph {
+    /// Builds the graph from every map in the tree and every common event in the database.
+    #[must_use]
+    pub fn new(project: &Project) -> Self {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        let maps = project
+            .tree
+            .iter()
+            .flat_map(|tree| &tree.maps)
+            .filter(|(_, map)| matches!(map.r#type, lcf::lmt::MapType::Map));
+        for (id, map) in maps {
+            let from = Node::Map(*id);
+            nodes.push(NodeInfo {
+                node: from,
+                name: decode(&map.name),
+                automatic: false,
+            });
+
+            let Some(Ok(summary)) = project.summary(*id) else {
+                continue;
+            };
+            edges.extend(summary.transfers.iter().filter_map(|transfer| {
+                Some(Edge {
+                    from,
+                    to: Node::Map(u16::try_from(transfer.map).ok()?),
+                    event: Some(transfer.event),
+                    x: Some(transfer.x),
+                    y: Some(transfer.y),
+                })
+            }));
+            edges.extend(summary.common_events.iter().map(|common_event| Edge {
+                from,
+                to: Node::CommonEvent(*common_event),
+                event: None,
+                x: None,
+                y: None,
+            }));
+        }
+
+        let common_events = project
+            .database
+            .iter()
+            .flat_map(|database| &database.common_events);
+        for common_event in common_events {
+            let from = Node::CommonEvent(common_event.id);
+            nodes.push(NodeInfo {
+                node: from,
+                name: decode(&common_event.name),
+                automatic: matches!(common_event.trigger, Trigger::Autorun | Trigger::Parallel),
+            });
+
+            for command in &common_event.commands {
+                let target = match command.instruction {
+                    Instruction::TransferPlayer { map, x, y, .. } => u16::try_from(map)
+                        .ok()
+                        .map(|map| (Node::Map(map), Some(x), Some(y))),
+                    Instruction::CallEvent { mode: 0, index, .. } => {
+                        Some((Node::CommonEvent(index), None, None))
+                    }
+                    _ => None,
+                };
+                if let Some((to, x, y)) = target {
+                    edges.push(Edge {
+                        from,
+                        to,
+                        event: None,
+                        x,
+                        y,
+                    });
+                }
+            }
+        }
+
+        let mut outgoing = HashMap::<_, Vec<_>>::new();
+        for edge in &edges {
+            outgoing.entry(edge.from).or_default().push(edge.to);
+        }
+
+        Self {
+            nodes,
+            edges,
+            outgoing,
+        }
+    }
+
+    /// Every node that can be reached from the given ones, including themselves.
+    #[must_use]
+    pub fn reachable(&self, from: impl IntoIterator<Item = Node>) -> HashSet<Node> {
+        self.search(from, |_| true)
+    }
+
+    /// The other maps a map leads to, either directly or through the common events it calls.
+    #[must_use]
+    pub fn exits(&self, map: u16) -> HashSet<Node> {
+        let mut exits = self.search([Node::Map(map)], |node| {
+            node == Node::Map(map) || matches!(node, Node::CommonEvent(_))
+        });
+        exits.retain(|node| matches!(node, Node::Map(id) if *id != map));
+        exits
+    }
+
+    /// Breadth first search, only following the edges of nodes for which `expand` is true.
+    fn search(
+        &self,
+        from: impl IntoIterator<Item = Node>,
+        expand: impl Fn(Node) -> bool,
+    ) -> HashSet<Node> {
+        let mut seen = HashSet::new();
+        let mut queue = from.into_iter().collect::<VecDeque<_>>();
+        seen.extend(queue.iter().copied());
+
+        while let Some(node) = queue.pop_front() {
+            if !expand(node) {
+                continue;
+            }
+            for next in self.outgoing.get(&node).into_iter().flatten() {
+                if seen.insert(*next) {
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    pub fn write(&self, format: &Format, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        match format {
+            Format::Dot => self.write_dot(out),
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut *out, self)?;
+                writeln!(out)
+            }
+        }
+    }
+
+    /// Writes the graph for Graphviz, with repeated edges between the same nodes merged and
+    /// common events that neither lead anywhere nor are called left out.
+    fn write_dot(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        let connected = self
+            .edges
+            .iter()
+            .flat_map(|edge| [edge.from, edge.to])
+            .collect::<HashSet<_>>();
+
+        writeln!(out, "digraph world {{")?;
+        for info in &self.nodes {
+            if matches!(info.node, Node::CommonEvent(_)) && !connected.contains(&info.node) {
+                continue;
+            }
+
+            let shape = match info.node {
+                Node::Map(_) => "ellipse",
+                Node::CommonEvent(_) => "box",
+            };
+            writeln!(
+                out,
+                "    \"{}\" [label=\"{}: {}\", shape={shape}];",
+                info.node,
+                info.node,
+                info.name.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+
+        let mut written = HashSet::new();
+        for edge in &self.edges {
+            if written.insert((edge.from, edge.to)) {
+                writeln!(out, "    \"{}\" -> \"{}\";", edge.from, edge.to)?;
+            }
+        }
+        writeln!(out, "}}")
+    }
+}
+
+fn decode(name: &[u8]) -> String {
+    encoding_rs::SHIFT_JIS.decode(name).0.into_owned()
+}
@@ -2,6 +2,8 @@ use std::num::NonZeroU32;
 
 use lcf::lmu::event::Event;
 
+use crate::project::Project;
+
 pub const ALL: &[&'static dyn Lint] = &[
     &weather::WeatherLint,
     &tissues::TissueLint,
@@ -13,24 +15,119 @@ pub const ALL: &[&'static dyn Lint] = &[
     &blue_sign::BlueSignLint,
     &pade_transfer::PadeTransferLint,
     &parallel_erase::ParallelEraseLint,
+    &teleport::TeleportLint,
+    &landing::LandingLint,
+    &unreachable::UnreachableLint,
+    &dead_end::DeadEndLint,
+    &door::DoorLint,
+    &ownership::OwnershipLint,
+    &undefined_reference::UndefinedReferenceLint,
+    &missing_asset::MissingAssetLint,
+    &unused_asset::UnusedAssetLint,
+    &asset_name::AssetNameLint,
+    &map_files::MapFilesLint,
+    &lint_allow::LintAllowLint,
 ];
 
+mod asset_name;
 mod blue_sign;
 mod comment;
+mod dead_end;
+mod door;
+mod flow;
 mod instant_scroll;
+mod landing;
+mod lint_allow;
+mod map_files;
+mod missing_asset;
+mod ownership;
 mod pade_transfer;
 mod parallel_erase;
 mod show_picture;
 mod special_skills;
+pub mod suppression;
+mod teleport;
+#[cfg(test)]
+pub mod testing;
 mod tissues;
+mod undefined_reference;
+mod unreachable;
+mod unused_asset;
 mod v44_assignment;
 mod weather;
 
-pub trait Lint {
+pub trait Lint: Sync {
+    /// Stable identifier, e.g. `weather-v42-parity`. Never changes once published.
+    fn code(&self) -> &'static str;
+    /// Permanent numeric alias, shown as `L0001`. Never reused, even if the lint is removed.
+    fn number(&self) -> usize;
+    fn category(&self) -> Category;
     fn name(&self) -> &'static str;
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<Diagnostic>;
+    /// The level most diagnostics from this lint are reported at.
+    fn default_level(&self) -> DiagnosticLevel;
+    fn scope(&self) -> Scope {
+        Scope::Map
+    }
+    /// Checks a single map. Only called for [`Scope::Map`] lints.
+    fn test(&self, _context: &Context) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+    /// Checks the project as a whole. Only called for [`Scope::Project`] lints.
+    fn test_project(
+        &self,
+        _project: &Project,
+        _settings: &crate::config::Settings,
+    ) -> Vec<Diagnostic> {
+        Vec::new()
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    /// Runs on every map on its own.
+    Map,
+    /// Runs once per game folder. Skipped when only a single map is checked.
+    Project,
+}
+
+/// Everything a lint can look at while checking a single map.
+pub struct Context<'a> {
+    /// Parsed from the `MapXXXX.lmu` file name, if it follows that convention.
+    pub map_id: Option<u16>,
+    pub map: &'a lcf::lmu::LcfMapUnit,
+    pub project: &'a Project,
+    pub settings: &'a crate::config::Settings,
+}
+
+/// Looks up a lint by its code, its `L0001` alias or the bare number of that alias.
+#[must_use]
+pub fn find(name: &str) -> Option<&'static dyn Lint> {
+    let number = name
+        .strip_prefix(['L', 'l'])
+        .unwrap_or(name)
+        .parse::<usize>()
+        .ok();
+
+    ALL.iter()
+        .copied()
+        .find(|lint| lint.code() == name || Some(lint.number()) == number)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// The map behaves incorrectly in game.
+    Correctness,
+    /// The map causes unnecessary lag.
+    Performance,
+    /// Something that must be documented for other contributors is not.
+    Documentation,
+    /// A 2kki-specific convention is not followed.
+    Convention,
+    Style,
+}
+
+#[derive(Clone, serde::Serialize)]
 pub struct Diagnostic {
     pub level: DiagnosticLevel,
     pub event: Option<DiagnosticEvent>,
@@ -67,19 +164,49 @@ impl From<Diagnostic> for Vec<Diagnostic> {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DiagnosticLevel {
     Warning,
     Error,
 }
 
+#[derive(Clone, serde::Serialize)]
 pub struct DiagnosticEvent {
     id: NonZeroU32,
     x: u32,
     y: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     page: Option<DiagnosticPage>,
 }
 
 impl DiagnosticEvent {
+    #[must_use]
+    pub const fn id(&self) -> u32 {
+        self.id.get()
+    }
+
+    #[must_use]
+    pub const fn page(&self) -> Option<&DiagnosticPage> {
+        self.page.as_ref()
+    }
+
+    /// Formats the location as `EVxxxx/Pxx/Ixxxxx`, omitting the parts that are not known.
+    #[must_use]
+    pub fn logical_location(&self) -> String {
+        match &self.page {
+            None => format!("EV{:04}", self.id),
+            Some(DiagnosticPage {
+                id: page,
+                command: None,
+            }) => format!("EV{:04}/P{page:02}", self.id),
+            Some(DiagnosticPage {
+                id: page,
+                command: Some(command),
+            }) => format!("EV{:04}/P{page:02}/I{command:05}", self.id),
+        }
+    }
+
     const fn with_page(mut self, page: DiagnosticPage) -> Self {
         self.page = Some(page);
         self
@@ -98,12 +225,34 @@ impl From<&Event> for DiagnosticEvent {
     }
 }
 
+#[derive(Clone, serde::Serialize)]
 pub struct DiagnosticPage {
     id: NonZeroU32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     command: Option<NonZeroU32>,
 }
 
 impl DiagnosticPage {
+    #[must_use]
+    pub const fn id(&self) -> u32 {
+        self.id.get()
+    }
+
+    /// Index of the command in the page's command list.
+    #[must_use]
+    pub const fn command_index(&self) -> Option<usize> {
+        match self.command {
+            Some(command) => Some(command.get() as usize - 1),
+            None => None,
+        }
+    }
+
+    /// Index of the page in the event's page list.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.id.get() as usize - 1
+    }
+
     #[must_use]
     pub const fn new_from_index(page_index: usize) -> Self {
         Self {
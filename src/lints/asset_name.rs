@@ -0,0 +1,67 @@
+//! The web port runs on Linux, where the case of file names matters, and its downloads break on
+//! names that do not convert cleanly between Shift-JIS and Unicode.
+
+pub struct AssetNameLint;
+
+impl super::Lint for AssetNameLint {
+    fn code(&self) -> &'static str {
+        "asset-name"
+    }
+
+    fn number(&self) -> usize {
+        20
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Referenced file names must match the files exactly"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let files = context.project.files(&context.settings.rtp);
+        let mut diagnostics = Vec::new();
+
+        super::missing_asset::references(context, |event, asset| {
+            let (name, malformed) = encoding_rs::SHIFT_JIS.decode_without_bom_handling(&asset.name);
+            let message = if malformed {
+                format!("The name of {} is not valid Shift-JIS", asset.path())
+            } else if *encoding_rs::SHIFT_JIS.encode(&name).0 != *asset.name {
+                format!(
+                    "The name of {} changes when converted back to Shift-JIS",
+                    asset.path()
+                )
+            } else {
+                // missing files are up to `missing-asset`
+                let Some(file) = files.find(asset) else {
+                    return;
+                };
+                let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+                if encoding_rs::SHIFT_JIS.encode(stem).2 {
+                    format!(
+                        "{}/{file} has characters that cannot be represented in Shift-JIS",
+                        asset.kind.dir()
+                    )
+                } else if stem != name {
+                    format!("{} is {}/{file} on disk", asset.path(), asset.kind.dir())
+                } else {
+                    return;
+                }
+            };
+
+            diagnostics.push(super::Diagnostic {
+                event,
+                level: super::DiagnosticLevel::Error,
+                message: Some(message),
+            });
+        });
+
+        diagnostics
+    }
+}
@@ -8,15 +8,31 @@ use crate::{
 pub struct BlueSignLint;
 
 impl super::Lint for BlueSignLint {
+    fn code(&self) -> &'static str {
+        "blue-sign-annotation"
+    }
+
+    fn number(&self) -> usize {
+        8
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Documentation
+    }
+
     fn name(&self) -> &'static str {
         "Blue signs must have annotations"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<Diagnostic> {
-        let exclusions = ["eserved", "予約", "接続"];
+    fn default_level(&self) -> DiagnosticLevel {
+        DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<Diagnostic> {
+        let exclusions = &context.settings.blue_sign_exclusions;
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             let is_blue_sign = event.pages.iter().any(|page| {
                 encoding_rs::SHIFT_JIS.decode(&page.graphic.file).0 == "system_kyouyu_gazou06"
                     && (page.graphic.index == 1 || page.graphic.index == 2)
@@ -37,7 +53,7 @@ impl super::Lint for BlueSignLint {
                         let comment = encoding_rs::SHIFT_JIS.decode(&comment).0;
                         exclusions
                             .iter()
-                            .any(|exclusion| comment.contains(exclusion))
+                            .any(|exclusion| comment.contains(exclusion.as_str()))
                     });
 
                 if !allowed {
@@ -3,19 +3,38 @@ use lcf::raw::lmu::event::instruction::Instruction;
 pub struct CommentLint;
 
 impl super::Lint for CommentLint {
+    fn code(&self) -> &'static str {
+        "comment-length"
+    }
+
+    fn number(&self) -> usize {
+        6
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Style
+    }
+
     fn name(&self) -> &'static str {
         "Comments should not be too long"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
                 for (command_index, command) in page.commands.iter().enumerate() {
                     match command.instruction {
                         Instruction::Comment | Instruction::CommentNextLine => {
-                            let max = 56u32.saturating_sub(command.indent * 2);
+                            let max = context
+                                .settings
+                                .comment_max_length
+                                .saturating_sub(command.indent * 2);
                             let len = encoding_rs::SHIFT_JIS
                                 .decode(&command.string)
                                 .0
@@ -0,0 +1,57 @@
+use crate::{graph::Node, project::Project};
+
+pub struct DeadEndLint;
+
+impl super::Lint for DeadEndLint {
+    fn code(&self) -> &'static str {
+        "dead-end-map"
+    }
+
+    fn number(&self) -> usize {
+        14
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Maps must have an exit"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn scope(&self) -> super::Scope {
+        super::Scope::Project
+    }
+
+    fn test_project(
+        &self,
+        project: &Project,
+        _settings: &crate::config::Settings,
+    ) -> Vec<super::Diagnostic> {
+        let graph = project.graph();
+
+        // common events that run on their own are left out, as they would be an exit of every map
+        graph
+            .nodes
+            .iter()
+            .filter_map(|info| match info.node {
+                // unreadable maps are reported on their own
+                Node::Map(id) if matches!(project.summary(id), Some(Ok(_))) => Some((id, info)),
+                _ => None,
+            })
+            .filter(|(id, _)| graph.exits(*id).is_empty())
+            .map(|(_, info)| super::Diagnostic {
+                event: None,
+                level: super::DiagnosticLevel::Warning,
+                message: Some(format!(
+                    "{} ({}) has no transfer to another map",
+                    info.node, info.name
+                )),
+            })
+            .collect()
+    }
+}
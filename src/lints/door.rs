@@ -0,0 +1,93 @@
+use lcf::{enums::Trigger, raw::lmu::event::instruction::Instruction};
+
+pub struct DoorLint;
+
+impl super::Lint for DoorLint {
+    fn code(&self) -> &'static str {
+        "two-way-door"
+    }
+
+    fn number(&self) -> usize {
+        15
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Convention
+    }
+
+    fn name(&self) -> &'static str {
+        "Doors should lead back to where they were entered"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let Some(here) = context.map_id else {
+            return Vec::new();
+        };
+        let tolerance = context.settings.door_tolerance;
+
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                // doors are walked into or interacted with, everything else is a cutscene
+                if !matches!(
+                    page.trigger,
+                    Trigger::ActionButton | Trigger::PlayerTouch | Trigger::EventTouch
+                ) {
+                    continue;
+                }
+
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    let Instruction::TransferPlayer { map, .. } = command.instruction else {
+                        continue;
+                    };
+                    // transfers within a map and to missing maps are not doors between maps
+                    let Some(Ok(destination)) = u16::try_from(map)
+                        .ok()
+                        .filter(|map| *map != here)
+                        .and_then(|map| context.project.summary(map))
+                    else {
+                        continue;
+                    };
+
+                    // the closest of the transfers back, measured in steps diagonally
+                    let closest = destination
+                        .transfers
+                        .iter()
+                        .filter(|transfer| transfer.map == u32::from(here))
+                        .map(|transfer| {
+                            let distance = transfer
+                                .x
+                                .abs_diff(event.x)
+                                .max(transfer.y.abs_diff(event.y));
+                            (distance, transfer)
+                        })
+                        .min_by_key(|(distance, _)| *distance);
+
+                    let message = match closest {
+                        None => format!("Map{map:04} has no transfer back to this map"),
+                        Some((distance, transfer)) if distance > tolerance => format!(
+                            "The closest way back, EV{:04} on Map{map:04}, lands at (X{:03}, Y{:03}), {distance} tiles away",
+                            transfer.event, transfer.x, transfer.y
+                        ),
+                        Some(_) => continue,
+                    };
+
+                    diagnostics.push(super::Diagnostic {
+                        event: Some(super::DiagnosticEvent::from(event).with_page(
+                            super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                        )),
+                        level: super::DiagnosticLevel::Warning,
+                        message: Some(message),
+                    });
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
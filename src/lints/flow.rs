@@ -0,0 +1,407 @@
+//! Control flow of a list of event commands.
+//!
+//! Every command is a node, plus one more node past the last command for the end of the list.
+//! Conditional branches, choices, loops, labels and the instructions stopping the event are
+//! followed; everything else, calls included, simply runs into the next command, and it is up to
+//! the lint to account for what a call does, with [`Call`] finding the commands it runs.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use lcf::raw::lmu::event::{command::Command, instruction::Instruction};
+
+/// Event id meaning the event doing the call.
+pub const THIS_EVENT: u32 = 10005;
+
+pub struct Flow<'a> {
+    commands: &'a [Command],
+    next: Vec<Vec<usize>>,
+}
+
+enum Open {
+    Branch { at: usize, otherwise: Option<usize> },
+    Choice { at: usize, options: Vec<usize> },
+    Loop { at: usize, breaks: Vec<usize> },
+}
+
+impl<'a> Flow<'a> {
+    /// The flow of the commands on their own. Calls are not followed, but run into the next
+    /// command; [`Call`] finds what they run for lints that need to follow them.
+    #[must_use]
+    pub fn new(commands: &'a [Command]) -> Self {
+        let end = commands.len();
+        let mut next = (0..end).map(|index| vec![index + 1]).collect::<Vec<_>>();
+        next.push(Vec::new());
+
+        // jumps go to the first label with the value
+        let labels = commands
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(index, command)| match command.instruction {
+                Instruction::Label { value } => Some((value, index)),
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut open = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            match command.instruction {
+                Instruction::ConditionalBranch { .. } => open.push(Open::Branch {
+                    at: index,
+                    otherwise: None,
+                }),
+                Instruction::ElseBranch => {
+                    if let Some(Open::Branch { otherwise, .. }) = open.last_mut() {
+                        *otherwise = Some(index);
+                    }
+                }
+                Instruction::EndBranch { .. } => {
+                    if let Some(Open::Branch { at, otherwise }) = open.pop_if(is_branch) {
+                        next[at] = vec![at + 1, otherwise.map_or(index, |otherwise| otherwise + 1)];
+                        if let Some(otherwise) = otherwise {
+                            next[otherwise] = vec![index];
+                        }
+                    }
+                }
+                Instruction::ShowChoice { .. } => open.push(Open::Choice {
+                    at: index,
+                    options: Vec::new(),
+                }),
+                Instruction::ShowChoiceOption { .. } => {
+                    if let Some(Open::Choice { options, .. }) = open.last_mut() {
+                        options.push(index);
+                    }
+                }
+                Instruction::ShowChoiceEnd => {
+                    if let Some(Open::Choice { at, options }) = open.pop_if(is_choice)
+                        && !options.is_empty()
+                    {
+                        next[at] = options.iter().map(|option| option + 1).collect();
+                        // the end of one option runs into the next one
+                        for option in options {
+                            next[option] = vec![index];
+                        }
+                    }
+                }
+                Instruction::Loop { .. } => open.push(Open::Loop {
+                    at: index,
+                    breaks: Vec::new(),
+                }),
+                Instruction::BreakLoop => {
+                    let innermost = open.iter_mut().rev().find_map(|construct| match construct {
+                        Open::Loop { breaks, .. } => Some(breaks),
+                        _ => None,
+                    });
+                    if let Some(breaks) = innermost {
+                        breaks.push(index);
+                    }
+                }
+                Instruction::EndLoop { .. } => {
+                    if let Some(Open::Loop { at, breaks }) = open.pop_if(is_loop) {
+                        next[index] = vec![at + 1];
+                        for r#break in breaks {
+                            next[r#break] = vec![index + 1];
+                        }
+                    }
+                }
+                Instruction::JumpToLabel { value } => {
+                    if let Some(label) = labels.get(&value) {
+                        next[index] = vec![*label];
+                    }
+                }
+                Instruction::EndEventProcessing | Instruction::EraseEvent => {
+                    next[index] = vec![end];
+                }
+                _ => (),
+            }
+        }
+
+        Self { commands, next }
+    }
+
+    /// The node for the end of the list.
+    #[must_use]
+    pub const fn end(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// The nodes that can run right after a node.
+    #[must_use]
+    pub fn next(&self, node: usize) -> &[usize] {
+        &self.next[node]
+    }
+
+    /// Which nodes can be reached from any of `from`, themselves included, without going through a
+    /// command for which `stops` is true. Such commands are reached, but not gone past.
+    pub fn reachable(&self, from: &[usize], stops: impl Fn(&Command) -> bool) -> Vec<bool> {
+        let mut seen = vec![false; self.next.len()];
+        let mut queue = Vec::new();
+        for node in from {
+            if !seen[*node] {
+                seen[*node] = true;
+                queue.push(*node);
+            }
+        }
+
+        while let Some(node) = queue.pop() {
+            if self.commands.get(node).is_some_and(&stops) {
+                continue;
+            }
+            for next in &self.next[node] {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    queue.push(*next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// Whether every way from a node to the end of the list goes through a command for which `is`
+    /// is true, not counting the node itself.
+    pub fn always_followed_by(&self, node: usize, is: impl Fn(&Command) -> bool) -> bool {
+        !self.reachable(self.next(node), is)[self.end()]
+    }
+
+    /// Runs through every way of the list with a state that each command can change, or split up
+    /// when it may go several ways. Returns the states each node can be reached with, stopping
+    /// once no new ones turn up.
+    pub fn states<S: Clone + Eq + Hash>(
+        &self,
+        initial: S,
+        mut visit: impl FnMut(usize, &Command, S) -> Vec<S>,
+    ) -> Vec<HashSet<S>> {
+        let mut states = vec![HashSet::new(); self.next.len()];
+        states[0].insert(initial.clone());
+        let mut queue = vec![(0, initial)];
+
+        while let Some((node, state)) = queue.pop() {
+            let Some(command) = self.commands.get(node) else {
+                continue;
+            };
+            for after in visit(node, command, state) {
+                for next in &self.next[node] {
+                    if states[*next].insert(after.clone()) {
+                        queue.push((*next, after.clone()));
+                    }
+                }
+            }
+        }
+
+        states
+    }
+}
+
+/// An event run by a `CallEvent` command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Call {
+    CommonEvent(u32),
+    MapEvent { event: u32, page: u32 },
+}
+
+impl Call {
+    /// The event an instruction calls, if it is known without running the game. `this` is the
+    /// event the instruction belongs to, or the one that called its common event.
+    #[must_use]
+    pub const fn of(instruction: &Instruction, this: u32) -> Option<Self> {
+        match *instruction {
+            Instruction::CallEvent { mode: 0, index, .. } => Some(Self::CommonEvent(index)),
+            Instruction::CallEvent {
+                mode: 1,
+                index,
+                page,
+            } => Some(Self::map_event(index, page, this)),
+            _ => None,
+        }
+    }
+
+    /// A call of a page of a map event, with [`THIS_EVENT`] standing for `this`.
+    #[must_use]
+    pub const fn map_event(event: u32, page: u32, this: u32) -> Self {
+        Self::MapEvent {
+            event: if event == THIS_EVENT { this } else { event },
+            page,
+        }
+    }
+
+    /// The commands the called event runs, if there is such an event.
+    #[must_use]
+    pub fn commands<'a>(self, context: &super::Context<'a>) -> Option<&'a [Command]> {
+        match self {
+            Self::CommonEvent(id) => context
+                .project
+                .database
+                .iter()
+                .flat_map(|database| &database.common_events)
+                .find(|common_event| common_event.id == id)
+                .map(|common_event| common_event.commands.as_slice()),
+            Self::MapEvent { event, page } => context
+                .map
+                .events
+                .iter()
+                .find(|candidate| candidate.id == event)
+                .and_then(|event| event.pages.get((page as usize).checked_sub(1)?))
+                .map(|page| page.commands.as_slice()),
+        }
+    }
+}
+
+const fn is_branch(open: &mut Open) -> bool {
+    matches!(open, Open::Branch { .. })
+}
+
+const fn is_choice(open: &mut Open) -> bool {
+    matches!(open, Open::Choice { .. })
+}
+
+const fn is_loop(open: &mut Open) -> bool {
+    matches!(open, Open::Loop { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(instructions: Vec<Instruction>) -> Vec<Command> {
+        instructions
+            .into_iter()
+            .map(|instruction| Command {
+                indent: 0,
+                string: Vec::new(),
+                instruction,
+            })
+            .collect()
+    }
+
+    const fn branch(has_else: u32) -> Instruction {
+        Instruction::ConditionalBranch {
+            mode: 0,
+            field1: 1,
+            field2: 0,
+            field3: 0,
+            field4: 0,
+            has_else,
+        }
+    }
+
+    const fn end_branch() -> Instruction {
+        Instruction::EndBranch { unknown: None }
+    }
+
+    const fn wait() -> Instruction {
+        Instruction::Wait {
+            deciseconds: 1,
+            unknown: None,
+        }
+    }
+
+    const fn is_wait(command: &Command) -> bool {
+        matches!(command.instruction, Instruction::Wait { .. })
+    }
+
+    #[test]
+    fn nested_branch() {
+        let commands = commands(vec![
+            branch(1),
+            branch(0),
+            wait(),
+            Instruction::End,
+            end_branch(),
+            Instruction::End,
+            Instruction::ElseBranch,
+            Instruction::ShowText,
+            Instruction::End,
+            end_branch(),
+        ]);
+        let flow = Flow::new(&commands);
+
+        assert_eq!(flow.next(0), [1, 7]);
+        assert_eq!(flow.next(1), [2, 4]);
+        assert_eq!(flow.next(6), [9]);
+        assert_eq!(flow.next(9), [flow.end()]);
+
+        // the wait stops one way through the inner branch, the others go around it
+        let reachable = flow.reachable(&[0], is_wait);
+        assert!(reachable[2] && reachable[4] && reachable[7] && reachable[flow.end()]);
+        assert!(!reachable[3]);
+        assert!(!flow.always_followed_by(0, is_wait));
+        assert!(flow.always_followed_by(1, |command| matches!(
+            command.instruction,
+            Instruction::EndBranch { .. }
+        )));
+    }
+
+    #[test]
+    fn loop_with_break() {
+        let commands = commands(vec![
+            Instruction::Loop { unknown: None },
+            branch(0),
+            Instruction::BreakLoop,
+            Instruction::End,
+            end_branch(),
+            wait(),
+            Instruction::End,
+            Instruction::EndLoop { unknown: None },
+            Instruction::ShowText,
+        ]);
+        let flow = Flow::new(&commands);
+
+        assert_eq!(flow.next(7), [1]);
+        assert_eq!(flow.next(2), [8]);
+
+        // the end of the loop runs into its start again
+        assert!(flow.reachable(&[5], |_| false)[1]);
+        // only the break leaves the loop
+        assert!(flow.always_followed_by(0, |command| matches!(
+            command.instruction,
+            Instruction::BreakLoop
+        )));
+        assert!(
+            !flow.reachable(&[0], |command| matches!(
+                command.instruction,
+                Instruction::BreakLoop
+            ))[8]
+        );
+    }
+
+    #[test]
+    fn jump_to_label() {
+        let commands = commands(vec![
+            Instruction::Label { value: 1 },
+            Instruction::ShowText,
+            Instruction::JumpToLabel { value: 1 },
+            Instruction::Label { value: 1 },
+            wait(),
+        ]);
+        let flow = Flow::new(&commands);
+
+        // the first label with the value wins
+        assert_eq!(flow.next(2), [0]);
+        let reachable = flow.reachable(&[0], |_| false);
+        // the jump loops forever, so nothing after it runs
+        assert_eq!(reachable, [true, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn end_of_processing() {
+        let commands = commands(vec![
+            branch(0),
+            Instruction::EndEventProcessing,
+            Instruction::End,
+            end_branch(),
+            wait(),
+        ]);
+        let flow = Flow::new(&commands);
+
+        assert_eq!(flow.next(1), [flow.end()]);
+        let reachable = flow.reachable(&[1], |_| false);
+        assert_eq!(reachable, [false, true, false, false, false, true]);
+        // the wait is skipped when the branch is taken
+        assert!(!flow.always_followed_by(0, is_wait));
+        assert!(flow.always_followed_by(3, is_wait));
+    }
+}
@@ -3,14 +3,30 @@ use lcf::raw::lmu::event::instruction::Instruction;
 pub struct InstantScrollLint;
 
 impl super::Lint for InstantScrollLint {
+    fn code(&self) -> &'static str {
+        "instant-scroll"
+    }
+
+    fn number(&self) -> usize {
+        4
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Convention
+    }
+
     fn name(&self) -> &'static str {
         "CEV0294 should be used for instant scroll"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
                 for (command_index, command) in page.commands.iter().enumerate() {
                     if let Instruction::ScrollMap { speed, .. } = command.instruction
@@ -0,0 +1,209 @@
+//! Passability follows the game engine: the upper layer decides unless its tile is marked as being
+//! drawn above the player, in which case the lower layer does.
+
+use lcf::{enums::Priority, ldb::chipset::ChipSet, raw::lmu::event::instruction::Instruction};
+
+use crate::project::{MapSummary, PageSummary};
+
+/// First tile id of each block of the lower layer, and of the upper layer.
+const BLOCK_C: u16 = 3000;
+const BLOCK_D: u16 = 4000;
+const BLOCK_E: u16 = 5000;
+const BLOCK_F: u16 = 10000;
+
+/// The down, left, right and up flags. A tile without any of them cannot be walked on.
+const DIRECTIONS: u32 = 0x0F;
+/// Set on upper layer tiles that are drawn above the player and leave passability to the lower layer.
+const ABOVE: u32 = 0x10;
+
+pub struct LandingLint;
+
+impl super::Lint for LandingLint {
+    fn code(&self) -> &'static str {
+        "landing-tile"
+    }
+
+    fn number(&self) -> usize {
+        12
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Transfers must land on passable tiles"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let Some(database) = &context.project.database else {
+            return Vec::new();
+        };
+
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    let Instruction::TransferPlayer { map, x, y, .. } = command.instruction else {
+                        continue;
+                    };
+
+                    // missing maps and positions outside of them are reported by teleport-destination
+                    let Some(Ok(summary)) = u16::try_from(map)
+                        .ok()
+                        .and_then(|id| context.project.summary(id))
+                    else {
+                        continue;
+                    };
+                    let Some(chipset) = (summary.chipset as usize)
+                        .checked_sub(1)
+                        .and_then(|index| database.chipsets.get(index))
+                    else {
+                        continue;
+                    };
+
+                    if let Some(obstacle) = obstacle(summary, chipset, x, y) {
+                        diagnostics.push(super::Diagnostic {
+                            event: Some(super::DiagnosticEvent::from(event).with_page(
+                                super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                            )),
+                            level: super::DiagnosticLevel::Error,
+                            message: Some(format!(
+                                "Lands at (X{x:03}, Y{y:03}) on Map{map:04}, {obstacle}"
+                            )),
+                        });
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Describes what keeps the player from moving at a position, if anything.
+fn obstacle(summary: &MapSummary, chipset: &ChipSet, x: u32, y: u32) -> Option<String> {
+    let (lower, upper) = summary.tiles(x, y)?;
+    if !is_passable(chipset, lower, upper) {
+        return Some("which is impassable".to_string());
+    }
+
+    // the active page depends on the game state, so only events that block on every page count
+    summary
+        .events
+        .iter()
+        .find(|event| {
+            event.x == x
+                && event.y == y
+                && !event.pages.is_empty()
+                && event.pages.iter().all(|page| blocks(chipset, page))
+        })
+        .map(|event| format!("which is blocked by EV{:04}", event.id))
+}
+
+fn is_passable(chipset: &ChipSet, lower: u16, upper: u16) -> bool {
+    if let Some(flags) = upper
+        .checked_sub(BLOCK_F)
+        .and_then(|index| chipset.passability_upper.get(index as usize))
+    {
+        if flags & DIRECTIONS == 0 {
+            return false;
+        }
+        if flags & ABOVE == 0 {
+            return true;
+        }
+    }
+
+    let index = match lower {
+        BLOCK_E.. => lower - BLOCK_E + 18,
+        BLOCK_D.. => (lower - BLOCK_D) / 50 + 6,
+        BLOCK_C.. => (lower - BLOCK_C) / 50 + 3,
+        _ => lower / 1000,
+    };
+    // unknown tiles are given the benefit of the doubt
+    chipset
+        .passability_lower
+        .get(index as usize)
+        .is_none_or(|flags| flags & DIRECTIONS != 0)
+}
+
+fn blocks(chipset: &ChipSet, page: &PageSummary) -> bool {
+    match page.priority {
+        Priority::SameAsCharacters => true,
+        Priority::BelowCharacters => page
+            .tile
+            .and_then(|tile| chipset.passability_upper.get(tile as usize))
+            .is_some_and(|flags| flags & DIRECTIONS == 0),
+        Priority::AboveCharacters => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chipset where only one lower layer entry can be walked on.
+    fn lower_only(index: usize) -> ChipSet {
+        let mut chipset = ChipSet::default();
+        chipset.passability_lower[index] = DIRECTIONS;
+        chipset
+    }
+
+    #[test]
+    fn lower_blocks() {
+        for (tile, index) in [
+            (2999, 2),
+            (3000, 3),
+            (3050, 4),
+            (3100, 5),
+            (4000, 6),
+            (5000, 18),
+        ] {
+            assert!(is_passable(&lower_only(index), tile, 0), "tile {tile}");
+            assert!(!is_passable(&lower_only(index - 1), tile, 0), "tile {tile}");
+        }
+    }
+
+    #[test]
+    fn outside_of_map() {
+        // every tile of the map is impassable
+        let summary = MapSummary {
+            width: 20,
+            height: 15,
+            chipset: 1,
+            lower: vec![0; 20 * 15],
+            upper: vec![BLOCK_F; 20 * 15],
+            events: Vec::new(),
+            transfers: Vec::new(),
+            common_events: Vec::new(),
+            assets: Vec::new(),
+        };
+        let chipset = ChipSet::default();
+
+        assert!(obstacle(&summary, &chipset, 5, 3).is_some());
+        // past the end of a row, rather than on the next one
+        assert!(obstacle(&summary, &chipset, 25, 3).is_none());
+        assert!(obstacle(&summary, &chipset, 5, 15).is_none());
+        assert!(obstacle(&summary, &chipset, u32::MAX, u32::MAX).is_none());
+    }
+
+    #[test]
+    fn upper_block() {
+        let mut chipset = ChipSet::default();
+        chipset.passability_upper[0] = DIRECTIONS;
+        assert!(is_passable(&chipset, 0, BLOCK_F));
+
+        chipset.passability_upper[0] = DIRECTIONS | ABOVE;
+        assert!(!is_passable(&chipset, 0, BLOCK_F));
+        chipset.passability_lower[0] = DIRECTIONS;
+        assert!(is_passable(&chipset, 0, BLOCK_F));
+
+        chipset.passability_upper[0] = 0;
+        assert!(!is_passable(&chipset, 0, BLOCK_F));
+    }
+}
@@ -0,0 +1,57 @@
+use lcf::raw::lmu::event::instruction::Instruction;
+
+pub struct LintAllowLint;
+
+impl super::Lint for LintAllowLint {
+    fn code(&self) -> &'static str {
+        "unknown-lint-allow"
+    }
+
+    fn number(&self) -> usize {
+        22
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Suppression comments must name existing lints"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    if !matches!(
+                        command.instruction,
+                        Instruction::Comment | Instruction::CommentNextLine
+                    ) {
+                        continue;
+                    }
+
+                    let comment = encoding_rs::SHIFT_JIS.decode(&command.string).0;
+                    for name in super::suppression::unknown(&comment) {
+                        diagnostics.push(super::Diagnostic {
+                            event: Some(super::DiagnosticEvent::from(event).with_page(
+                                super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                            )),
+                            level: super::DiagnosticLevel::Warning,
+                            message: Some(format!(
+                                "There is no lint `{name}`, so nothing is suppressed"
+                            )),
+                        });
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
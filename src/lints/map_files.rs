@@ -0,0 +1,103 @@
+use crate::project::Project;
+
+pub struct MapFilesLint;
+
+impl super::Lint for MapFilesLint {
+    fn code(&self) -> &'static str {
+        "map-files"
+    }
+
+    fn number(&self) -> usize {
+        21
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Map files must match the map tree"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn scope(&self) -> super::Scope {
+        super::Scope::Project
+    }
+
+    fn test_project(
+        &self,
+        project: &Project,
+        _settings: &crate::config::Settings,
+    ) -> Vec<super::Diagnostic> {
+        let Some(tree) = &project.tree else {
+            return Vec::new();
+        };
+        let diagnostic = |level, message| super::Diagnostic {
+            event: None,
+            level,
+            message: Some(message),
+        };
+        let file = |id| {
+            project
+                .map_path(id)
+                .file_name()
+                .map_or_else(String::new, |file| file.to_string_lossy().into_owned())
+        };
+        let mut diagnostics = Vec::new();
+
+        diagnostics.extend(project.duplicate_maps.iter().map(|id| {
+            diagnostic(
+                super::DiagnosticLevel::Error,
+                format!("Map{id:04} is listed more than once in the map tree"),
+            )
+        }));
+
+        // the root is the game itself
+        for (id, map) in tree.maps.iter().skip(1) {
+            let name = encoding_rs::SHIFT_JIS.decode(&map.name).0;
+            let has_file = project.map_files.contains_key(id);
+            match map.r#type {
+                lcf::lmt::MapType::Map if !has_file => diagnostics.push(diagnostic(
+                    super::DiagnosticLevel::Error,
+                    format!("Map{id:04} ({name}) has no file"),
+                )),
+                lcf::lmt::MapType::Area { .. } if has_file => diagnostics.push(diagnostic(
+                    super::DiagnosticLevel::Warning,
+                    format!(
+                        "Map{id:04} ({name}) is an area, so {} is never used",
+                        file(*id)
+                    ),
+                )),
+                _ => (),
+            }
+
+            if u16::try_from(map.parent).map_or(true, |parent| !tree.maps.contains_key(&parent)) {
+                diagnostics.push(diagnostic(
+                    super::DiagnosticLevel::Error,
+                    format!(
+                        "Map{id:04} ({name}) is under Map{:04}, which does not exist",
+                        map.parent
+                    ),
+                ));
+            }
+        }
+
+        diagnostics.extend(
+            project
+                .map_files
+                .keys()
+                .filter(|id| !tree.maps.contains_key(*id))
+                .map(|id| {
+                    diagnostic(
+                        super::DiagnosticLevel::Warning,
+                        format!("{} is not in the map tree", file(*id)),
+                    )
+                }),
+        );
+
+        diagnostics
+    }
+}
@@ -0,0 +1,88 @@
+use crate::assets::{self, Asset, Kind};
+
+pub struct MissingAssetLint;
+
+impl super::Lint for MissingAssetLint {
+    fn code(&self) -> &'static str {
+        "missing-asset"
+    }
+
+    fn number(&self) -> usize {
+        18
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Referenced files must exist"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let files = context.project.files(&context.settings.rtp);
+        let mut diagnostics = Vec::new();
+
+        references(context, |event, asset| {
+            if files.find(asset).is_none() {
+                diagnostics.push(super::Diagnostic {
+                    event,
+                    level: super::DiagnosticLevel::Error,
+                    message: Some(format!("{} does not exist", asset.path())),
+                });
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// Calls `found` with every file the map refers to and where, which is nowhere in particular for
+/// the panorama and chipset of the map itself.
+pub fn references(
+    context: &super::Context,
+    mut found: impl FnMut(Option<super::DiagnosticEvent>, &Asset),
+) {
+    let map = context.map;
+    let panorama = map
+        .panorama
+        .file
+        .as_deref()
+        .filter(|_| map.panorama.enabled)
+        .and_then(|file| assets::named(Kind::Panorama, file));
+    // a chipset missing from the database has no file to look for
+    let chipset = (map.chipset as usize)
+        .checked_sub(1)
+        .and_then(|index| context.project.database.as_ref()?.chipsets.get(index))
+        .and_then(|chipset| assets::named(Kind::ChipSet, &chipset.file));
+    for asset in panorama.iter().chain(&chipset) {
+        found(None, asset);
+    }
+
+    for event in &map.events {
+        for (page_index, page) in event.pages.iter().enumerate() {
+            if let Some(asset) = assets::named(Kind::CharSet, &page.graphic.file) {
+                found(
+                    Some(
+                        super::DiagnosticEvent::from(event)
+                            .with_page(super::DiagnosticPage::new_from_index(page_index)),
+                    ),
+                    &asset,
+                );
+            }
+
+            assets::commands(&page.commands, |command_index, asset| {
+                found(
+                    Some(super::DiagnosticEvent::from(event).with_page(
+                        super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                    )),
+                    &asset,
+                );
+            });
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::xref::{self, Access};
+
+pub struct OwnershipLint;
+
+impl super::Lint for OwnershipLint {
+    fn code(&self) -> &'static str {
+        "range-ownership"
+    }
+
+    fn number(&self) -> usize {
+        16
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Convention
+    }
+
+    fn name(&self) -> &'static str {
+        "Switches and variables should only be written by their owner"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let settings = context.settings;
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                // batch writes are reported once for each range they reach into
+                let mut reported = Vec::new();
+
+                xref::commands(&page.commands, |command_index, kind, id, access| {
+                    if access != Access::Write {
+                        return;
+                    }
+                    let Some((range_index, range)) = settings
+                        .ownership
+                        .iter()
+                        .enumerate()
+                        .find(|(_, range)| range.contains(kind, id))
+                    else {
+                        return;
+                    };
+                    if reported.contains(&(command_index, range_index)) {
+                        return;
+                    }
+
+                    let name = kind.name(id);
+                    let (level, message) = if range.read_only {
+                        (
+                            super::DiagnosticLevel::Error,
+                            format!("{name} is read-only"),
+                        )
+                    } else if range.reserved {
+                        (super::DiagnosticLevel::Error, format!("{name} is reserved"))
+                    } else {
+                        match (&range.owner, &settings.owner) {
+                            (Some(owner), Some(me)) if owner != me => (
+                                super::DiagnosticLevel::Warning,
+                                format!("{name} belongs to {owner}"),
+                            ),
+                            _ => return,
+                        }
+                    };
+
+                    reported.push((command_index, range_index));
+                    diagnostics.push(super::Diagnostic {
+                        event: Some(super::DiagnosticEvent::from(event).with_page(
+                            super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                        )),
+                        level,
+                        message: Some(message),
+                    });
+                });
+            }
+        }
+
+        diagnostics
+    }
+}
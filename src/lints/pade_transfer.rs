@@ -1,57 +1,90 @@
-use lcf::raw::lmu::event::instruction::Instruction;
+//! Every way through the page is followed, as well as calls into the common events and map events
+//! they run, so that a PADE or unPADE done by a called event counts too. Calls to an event picked
+//! through variables can only be followed when the variables were set to a constant earlier on;
+//! otherwise what the call does is unknown, and it is assumed to leave the state as it is.
+
+use std::collections::{BTreeMap, HashMap};
+
+use lcf::raw::lmu::event::{command::Command, instruction::Instruction};
+
+use super::flow::{Call, Flow};
+
+/// Common events that disable and enable the player's actions during an event.
+const PADE: u32 = 8;
+const UNPADE: u32 = 9;
+/// Stands for the command calling an event while the called event is walked, so that what is
+/// found in it can be reported at any call.
+const CALLER: usize = usize::MAX;
 
 pub struct PadeTransferLint;
 
 impl super::Lint for PadeTransferLint {
+    fn code(&self) -> &'static str {
+        "pade-transfer"
+    }
+
+    fn number(&self) -> usize {
+        9
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
     fn name(&self) -> &'static str {
         "Transitioning maps should be unPADEed"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        let exclusions = ["移動先マップで直接「ｲﾍﾞﾝﾄ中動作禁止解除」しています。"];
+        let exclusions = &context.settings.pade_transfer_exclusions;
+        let mut walker = Walker {
+            context,
+            event: 0,
+            calls: Vec::new(),
+            cache: HashMap::new(),
+            recursed: false,
+        };
+
+        for event in &context.map.events {
+            // in a called event, this event still means the event of the page being checked
+            walker.event = event.id;
+            walker.cache.clear();
 
-        for event in &map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
-                let mut pade = false;
-                let mut moved = false;
-                let mut ignored = false;
-
-                for command in &page.commands {
-                    match command.instruction {
-                        Instruction::CallEvent { mode, index, .. } if mode == 0 && index == 8 => {
-                            // PADE
-                            pade = true;
-                        }
-                        Instruction::CallEvent { mode, index, .. } if mode == 0 && index == 9 => {
-                            // unPADE
-                            pade = false;
-                        }
-                        Instruction::TransferPlayer { .. } if pade => {
-                            moved = true;
-                        }
-                        Instruction::Comment | Instruction::CommentNextLine => {
-                            let string = encoding_rs::SHIFT_JIS.decode(&command.string).0;
-                            if !exclusions
-                                .iter()
-                                .any(|exclusion| string.contains(exclusion))
-                            {
-                                ignored = true;
-                            }
-                        }
-                        _ => (),
+                let excluded = page.commands.iter().any(|command| {
+                    matches!(
+                        command.instruction,
+                        Instruction::Comment | Instruction::CommentNextLine
+                    ) && {
+                        let string = encoding_rs::SHIFT_JIS.decode(&command.string).0;
+                        exclusions
+                            .iter()
+                            .any(|exclusion| string.contains(exclusion.as_str()))
                     }
+                });
+                if excluded {
+                    continue;
                 }
 
-                if pade && moved && !ignored {
+                let moved = walker
+                    .run(&page.commands, State::default(), None)
+                    .into_iter()
+                    .filter(|state| state.pade)
+                    .filter_map(|state| state.moved)
+                    .min();
+
+                if let Some(command_index) = moved {
                     diagnostics.push(super::Diagnostic {
-                        event: Some(
-                            super::DiagnosticEvent::from(event).with_page(
-                                crate::lints::DiagnosticPage::new_from_index(page_index),
-                            ),
-                        ),
-                        level: crate::lints::DiagnosticLevel::Error,
+                        event: Some(super::DiagnosticEvent::from(event).with_page(
+                            super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                        )),
+                        level: super::DiagnosticLevel::Error,
                         message: None,
                     });
                 }
@@ -61,3 +94,256 @@ impl super::Lint for PadeTransferLint {
         diagnostics
     }
 }
+
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+struct State {
+    pade: bool,
+    /// Where the player was transferred with their actions disabled, unless enabled again since.
+    moved: Option<usize>,
+    /// Variables known to hold a constant.
+    variables: BTreeMap<u32, u32>,
+}
+
+impl State {
+    const fn unpade(&mut self) {
+        self.pade = false;
+        self.moved = None;
+    }
+}
+
+struct Walker<'a> {
+    context: &'a super::Context<'a>,
+    /// The event whose page is being checked.
+    event: u32,
+    /// The events being called, to not follow recursive calls forever.
+    calls: Vec<Call>,
+    /// The states a called event can end with, by the state it starts with.
+    cache: HashMap<(Call, State), Vec<State>>,
+    /// Whether a recursive call was skipped since this was last reset, which makes what was
+    /// found depend on the calls being made and not fit for the cache.
+    recursed: bool,
+}
+
+impl Walker<'_> {
+    /// Every state the commands can end with when starting out with `state`. Inside of a called
+    /// event, `origin` is [`CALLER`], which is replaced with the index of the call on the page being
+    /// checked once the call returns.
+    fn run(&mut self, commands: &[Command], state: State, origin: Option<usize>) -> Vec<State> {
+        let flow = Flow::new(commands);
+        let mut states = flow.states(state, |index, command, state| {
+            self.step(origin.unwrap_or(index), command, state)
+        });
+        states.swap_remove(flow.end()).into_iter().collect()
+    }
+
+    fn step(&mut self, at: usize, command: &Command, mut state: State) -> Vec<State> {
+        if let Some(call) = Call::of(&command.instruction, self.event) {
+            return self.call(call, at, state);
+        }
+        match command.instruction {
+            Instruction::CallEvent {
+                mode: 2,
+                index,
+                page,
+            } => {
+                let event = state.variables.get(&index).copied();
+                let page = state.variables.get(&page).copied();
+                // otherwise whatever is called is unknown, and assumed to change nothing
+                if let Some((event, page)) = event.zip(page) {
+                    return self.call(Call::map_event(event, page, self.event), at, state);
+                }
+            }
+            Instruction::TransferPlayer { .. } if state.pade => {
+                state.moved.get_or_insert(at);
+            }
+            Instruction::ControlVariables {
+                mode,
+                start,
+                end,
+                operation,
+                operand,
+                value1,
+                ..
+            } => match mode {
+                0 | 1 => {
+                    let end = if mode == 0 { start } else { end };
+                    state
+                        .variables
+                        .retain(|variable, _| !(start..=end).contains(variable));
+                    if mode == 0 && operation == 0 && operand == 0 {
+                        state.variables.insert(start, value1);
+                    }
+                }
+                _ => state.variables.clear(),
+            },
+            _ => (),
+        }
+        vec![state]
+    }
+
+    fn call(&mut self, call: Call, at: usize, mut state: State) -> Vec<State> {
+        match call {
+            Call::CommonEvent(PADE) => {
+                state.pade = true;
+                return vec![state];
+            }
+            Call::CommonEvent(UNPADE) => {
+                state.unpade();
+                return vec![state];
+            }
+            Call::CommonEvent(_) | Call::MapEvent { .. } => (),
+        }
+        if self.calls.contains(&call) {
+            self.recursed = true;
+            return vec![state];
+        }
+
+        let key = (call, state);
+        let states = if let Some(states) = self.cache.get(&key) {
+            states.clone()
+        } else if let Some(commands) = call.commands(self.context) {
+            let recursed = std::mem::take(&mut self.recursed);
+            self.calls.push(call);
+            let states = self.run(commands, key.1.clone(), Some(CALLER));
+            self.calls.pop();
+            if !self.recursed {
+                self.cache.insert(key, states.clone());
+            }
+            self.recursed |= recursed;
+            states
+        } else {
+            vec![key.1]
+        };
+
+        states
+            .into_iter()
+            .map(|mut state| {
+                if state.moved == Some(CALLER) {
+                    state.moved = Some(at);
+                }
+                state
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lcf::enums::Trigger;
+
+    use super::*;
+    use crate::lints::{
+        flow::THIS_EVENT,
+        testing::{commands, map, page, places, project, test},
+    };
+
+    const TRANSFER: Instruction = Instruction::TransferPlayer {
+        map: 2,
+        x: 0,
+        y: 0,
+        direction: None,
+    };
+
+    const fn call(index: u32) -> Instruction {
+        Instruction::CallEvent {
+            mode: 0,
+            index,
+            page: 0,
+        }
+    }
+
+    /// Checks the map events, given as the instructions of their pages, with the common events
+    /// numbered from 1 and PADE and unPADE added as needed.
+    fn check(
+        events: Vec<Vec<Vec<Instruction>>>,
+        mut common_events: Vec<Vec<Instruction>>,
+    ) -> Vec<(u32, u32, u32)> {
+        common_events.resize(UNPADE as usize, Vec::new());
+        let map = map(events
+            .into_iter()
+            .map(|pages| {
+                pages
+                    .into_iter()
+                    .map(|instructions| page(Trigger::ActionButton, commands(instructions)))
+                    .collect()
+            })
+            .collect());
+        let project = project(common_events.into_iter().map(commands).collect());
+        places(&test(&PadeTransferLint, &map, &project))
+    }
+
+    #[test]
+    fn pade() {
+        assert_eq!(
+            check(vec![vec![vec![call(PADE), TRANSFER]]], Vec::new()),
+            [(1, 1, 2)]
+        );
+        assert!(
+            check(
+                vec![vec![vec![call(PADE), call(UNPADE), TRANSFER]]],
+                Vec::new()
+            )
+            .is_empty()
+        );
+        assert!(check(vec![vec![vec![TRANSFER, call(PADE)]]], Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn through_common_event() {
+        assert_eq!(
+            check(vec![vec![vec![call(1), TRANSFER]]], vec![vec![call(PADE)]]),
+            [(1, 1, 2)]
+        );
+        // a transfer in the called event is reported at the call
+        assert_eq!(
+            check(vec![vec![vec![call(PADE), call(1)]]], vec![vec![TRANSFER]]),
+            [(1, 1, 2)]
+        );
+        assert!(
+            check(
+                vec![vec![vec![call(PADE), call(1), TRANSFER]]],
+                vec![vec![call(UNPADE)]]
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn recursive_common_event() {
+        assert_eq!(
+            check(
+                vec![vec![vec![call(1), TRANSFER]]],
+                vec![vec![call(1), call(PADE), call(1)]]
+            ),
+            [(1, 1, 2)]
+        );
+        assert!(check(vec![vec![vec![call(1), TRANSFER]]], vec![vec![call(1)]]).is_empty());
+    }
+
+    #[test]
+    fn this_event() {
+        let this_event_page_2 = Instruction::CallEvent {
+            mode: 1,
+            index: THIS_EVENT,
+            page: 2,
+        };
+        // in a common event, this event is the one whose page is checked
+        assert_eq!(
+            check(
+                vec![
+                    vec![vec![call(1), TRANSFER], vec![]],
+                    vec![vec![call(1), TRANSFER], vec![call(PADE)]],
+                ],
+                vec![vec![this_event_page_2.clone()]]
+            ),
+            [(2, 1, 2)]
+        );
+        assert_eq!(
+            check(
+                vec![vec![vec![this_event_page_2, TRANSFER], vec![call(PADE)]]],
+                Vec::new()
+            ),
+            [(1, 1, 2)]
+        );
+    }
+}
@@ -1,46 +1,85 @@
-use lcf::{enums::Trigger, raw::lmu::event::instruction::Instruction};
+//! A parallel page runs again from the top as soon as it finishes, so an instruction is laggy when
+//! some way through the page runs it again on the next frame without anything in between that
+//! erases the event, waits, transfers the player, or changes a switch or variable that a page
+//! condition of the event or a conditional branch of the page depends on. Any other switch cannot
+//! keep the page from running. A called event counts when every way through it does one of these.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use lcf::{
+    enums::Trigger,
+    lmu::event::Event,
+    raw::lmu::event::{command::Command, instruction::Instruction},
+};
+
+use super::flow::{Call, Flow};
+use crate::xref::{Access, Kind};
 
 pub struct ParallelEraseLint;
 
 impl super::Lint for ParallelEraseLint {
+    fn code(&self) -> &'static str {
+        "parallel-erase"
+    }
+
+    fn number(&self) -> usize {
+        10
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Performance
+    }
+
     fn name(&self) -> &'static str {
         "Laggy parallel events should be erased after running"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
                 if page.trigger != Trigger::Parallel {
                     continue;
                 }
 
-                let mut has_laggy_instruction = false;
-                let mut is_erased = false;
+                let stops = Stops {
+                    context,
+                    event: event.id,
+                    conditions: conditions(event, &page.commands),
+                    calls: RefCell::default(),
+                    cache: RefCell::default(),
+                };
+                let stops = |command: &Command| stops.stops(command);
+
+                let flow = Flow::new(&page.commands);
+                let reachable = flow.reachable(&[0], stops);
 
-                for command in &page.commands {
-                    match command.instruction {
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    let laggy = matches!(
+                        command.instruction,
                         Instruction::PlayBGM { .. }
-                        | Instruction::MovePicture { .. }
-                        | Instruction::ShowPicture { .. } => {
-                            has_laggy_instruction = true;
-                        }
-                        Instruction::EraseEvent { .. } => {
-                            is_erased = true;
-                        }
-                        _ => (),
+                            | Instruction::MovePicture { .. }
+                            | Instruction::ShowPicture { .. }
+                    );
+                    // runs again on the next frame, or right away through a loop or jump
+                    if !laggy
+                        || !reachable[command_index]
+                        || flow.always_followed_by(command_index, stops)
+                            && !flow.reachable(flow.next(command_index), stops)[command_index]
+                    {
+                        continue;
                     }
-                }
 
-                if has_laggy_instruction && !is_erased {
                     diagnostics.push(super::Diagnostic {
-                        event: Some(
-                            super::DiagnosticEvent::from(event).with_page(
-                                crate::lints::DiagnosticPage::new_from_index(page_index),
-                            ),
-                        ),
-                        level: crate::lints::DiagnosticLevel::Warning,
+                        event: Some(super::DiagnosticEvent::from(event).with_page(
+                            super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                        )),
+                        level: super::DiagnosticLevel::Warning,
                         message: None,
                     });
                 }
@@ -50,3 +89,172 @@ impl super::Lint for ParallelEraseLint {
         diagnostics
     }
 }
+
+/// The switches and variables that decide which page of the event is active, or which way the
+/// conditional branches of the commands go.
+fn conditions(event: &Event, commands: &[Command]) -> Vec<(Kind, u32)> {
+    let mut conditions = Vec::new();
+    for condition in event.pages.iter().map(|page| &page.condition) {
+        for (enabled, switch) in [condition.switch_a, condition.switch_b] {
+            if enabled {
+                conditions.push((Kind::Switch, switch));
+            }
+        }
+        if condition.variable.0 {
+            conditions.push((Kind::Variable, condition.variable.1));
+        }
+    }
+    crate::xref::commands(commands, |index, kind, id, _| {
+        if matches!(
+            commands[index].instruction,
+            Instruction::ConditionalBranch { .. }
+        ) {
+            conditions.push((kind, id));
+        }
+    });
+    conditions
+}
+
+struct Stops<'a> {
+    context: &'a super::Context<'a>,
+    /// The event whose page is being checked.
+    event: u32,
+    /// The switches and variables the page depends on.
+    conditions: Vec<(Kind, u32)>,
+    /// The events being called, to not follow recursive calls forever.
+    calls: RefCell<Vec<Call>>,
+    /// Whether every way through a called event stops.
+    cache: RefCell<HashMap<Call, bool>>,
+}
+
+impl Stops<'_> {
+    /// Whether the page is not run again right away after the command.
+    fn stops(&self, command: &Command) -> bool {
+        let picked = match command.instruction {
+            Instruction::EraseEvent
+            | Instruction::Wait { .. }
+            | Instruction::WaitForAllMovement
+            | Instruction::ShowText
+            // the parallel processes of the map end along with it
+            | Instruction::TransferPlayer { .. } => return true,
+            // the switch or variable is picked through another variable, so it could be any of them
+            Instruction::ControlSwitches { mode: 2, .. } => Some(Kind::Switch),
+            Instruction::ControlVariables { mode: 2, .. } => Some(Kind::Variable),
+            _ => None,
+        };
+        if let Some(picked) = picked
+            && self.conditions.iter().any(|(kind, _)| *kind == picked)
+        {
+            return true;
+        }
+        if let Some(call) = Call::of(&command.instruction, self.event) {
+            return self.call(call);
+        }
+
+        let mut changes = false;
+        crate::xref::commands(std::slice::from_ref(command), |_, kind, id, access| {
+            changes |= access == Access::Write && self.conditions.contains(&(kind, id));
+        });
+        changes
+    }
+
+    /// Whether every way through a called event stops. Recursive calls do not.
+    fn call(&self, call: Call) -> bool {
+        if let Some(stops) = self.cache.borrow().get(&call) {
+            return *stops;
+        }
+        let Some(commands) = call.commands(self.context) else {
+            return false;
+        };
+        if self.calls.borrow().contains(&call) {
+            return false;
+        }
+
+        self.calls.borrow_mut().push(call);
+        let flow = Flow::new(commands);
+        let stops = !flow.reachable(&[0], |command| self.stops(command))[flow.end()];
+        self.calls.borrow_mut().pop();
+        // what is found below a recursive call depends on the calls being made
+        if self.calls.borrow().is_empty() {
+            self.cache.borrow_mut().insert(call, stops);
+        }
+        stops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lints::testing::{commands, map, page, places, project, test};
+
+    const PLAY_BGM: Instruction = Instruction::PlayBGM {
+        fade_in: 0,
+        volume: 100,
+        tempo: 100,
+        balance: 50,
+    };
+
+    const fn call(index: u32) -> Instruction {
+        Instruction::CallEvent {
+            mode: 0,
+            index,
+            page: 0,
+        }
+    }
+
+    fn check(instructions: Vec<Instruction>, common_events: Vec<Vec<Instruction>>) -> Vec<u32> {
+        let map = map(vec![vec![page(Trigger::Parallel, commands(instructions))]]);
+        let project = project(common_events.into_iter().map(commands).collect());
+        places(&test(&ParallelEraseLint, &map, &project))
+            .into_iter()
+            .map(|(_, _, command)| command)
+            .collect()
+    }
+
+    #[test]
+    fn laggy() {
+        assert_eq!(check(vec![PLAY_BGM], Vec::new()), [1]);
+    }
+
+    #[test]
+    fn transfer() {
+        let transfer = Instruction::TransferPlayer {
+            map: 2,
+            x: 0,
+            y: 0,
+            direction: None,
+        };
+        assert!(check(vec![PLAY_BGM, transfer], Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn called_common_event() {
+        let wait = Instruction::Wait {
+            deciseconds: 1,
+            unknown: None,
+        };
+        assert!(check(vec![PLAY_BGM, call(1)], vec![vec![wait]]).is_empty());
+        assert!(
+            check(
+                vec![PLAY_BGM, call(2)],
+                vec![vec![], vec![Instruction::EraseEvent]]
+            )
+            .is_empty()
+        );
+        // calls into events that do not stop, or do not exist, change nothing
+        assert_eq!(check(vec![PLAY_BGM, call(1)], vec![vec![]]), [1]);
+        assert_eq!(check(vec![PLAY_BGM, call(3)], vec![vec![]]), [1]);
+    }
+
+    #[test]
+    fn recursive_common_event() {
+        assert_eq!(check(vec![PLAY_BGM, call(1)], vec![vec![call(1)]]), [1]);
+        assert!(
+            check(
+                vec![PLAY_BGM, call(1)],
+                vec![vec![call(2)], vec![Instruction::EraseEvent]]
+            )
+            .is_empty()
+        );
+    }
+}
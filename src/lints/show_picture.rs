@@ -3,15 +3,31 @@ use lcf::raw::lmu::event::instruction::Instruction;
 pub struct ShowPictureLint;
 
 impl super::Lint for ShowPictureLint {
+    fn code(&self) -> &'static str {
+        "prefer-move-picture"
+    }
+
+    fn number(&self) -> usize {
+        7
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Performance
+    }
+
     // "Also, although the behavior of 'Show Picture' looks the same as 'Move Picture,' it is preferable to use 'Move Picture,' as it is lighter in terms of processing load."
     fn name(&self) -> &'static str {
         "MovePicture is preferrable to ShowPicture"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
                 for (command_index, command) in page.commands.iter().enumerate() {
                     if let Instruction::ShowPicture { .. } = command.instruction {
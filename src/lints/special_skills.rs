@@ -1,49 +1,76 @@
+use std::collections::BTreeMap;
+
 use lcf::raw::lmu::event::instruction::Instruction;
 
 pub struct SpecialSkillsLint;
 
 impl super::Lint for SpecialSkillsLint {
+    fn code(&self) -> &'static str {
+        "special-skill-annotation"
+    }
+
+    fn number(&self) -> usize {
+        5
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Documentation
+    }
+
     fn name(&self) -> &'static str {
         "Special skill usage must be annotated"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
-        let mut excused = false;
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let skills = &context.project.names.skills;
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
+                // an annotation only covers the conditional branch right after it
+                let mut annotation = None;
+
                 for (command_index, command) in page.commands.iter().enumerate() {
                     match command.instruction {
                         Instruction::Comment | Instruction::CommentNextLine => {
-                            // TODO: in the future, this should validate that the skill it says
-                            //       it is checking is the same one that it is actually checking.
-                            if encoding_rs::SHIFT_JIS
-                                .decode(&command.string)
-                                .0
-                                .contains("▽Skills")
-                            {
-                                excused = true;
+                            let comment = encoding_rs::SHIFT_JIS.decode(&command.string).0;
+                            if let Some((_, rest)) = comment.split_once("▽Skills") {
+                                annotation = Some(Annotation::parse(rest));
                             }
                         }
                         Instruction::ConditionalBranch {
                             mode,   // 5 == actor
                             field1, // 2 == map completion (2kki specific)
                             field2, // 4 == knows skill
+                            field3, // skill id
                             ..
-                        } if mode == 5 && field1 == 2 && field2 == 4 => {
-                            if !excused {
-                                diagnostics.push(super::Diagnostic {
-                                    event: Some(super::DiagnosticEvent::from(event).with_page(
-                                        super::DiagnosticPage::new_from_indexes(
-                                            page_index,
-                                            command_index,
-                                        ),
-                                    )),
-                                    level: super::DiagnosticLevel::Error,
-                                    message: None,
-                                });
+                        } => {
+                            let annotation = annotation.take();
+                            if !(mode == 5 && field1 == 2 && field2 == 4) {
+                                continue;
                             }
+
+                            let message = match annotation {
+                                None => None,
+                                Some(annotation) => match annotation.mismatch(skills, field3) {
+                                    Some(message) => Some(message),
+                                    None => continue,
+                                },
+                            };
+                            diagnostics.push(super::Diagnostic {
+                                event: Some(super::DiagnosticEvent::from(event).with_page(
+                                    super::DiagnosticPage::new_from_indexes(
+                                        page_index,
+                                        command_index,
+                                    ),
+                                )),
+                                level: super::DiagnosticLevel::Error,
+                                message,
+                            });
                         }
                         _ => (),
                     }
@@ -54,3 +81,120 @@ impl super::Lint for SpecialSkillsLint {
         diagnostics
     }
 }
+
+/// The skill an annotation says is checked, written after `▽Skills` as either its id or its name.
+#[derive(Debug, PartialEq, Eq)]
+enum Annotation {
+    Unspecified,
+    Id(u32),
+    Name(String),
+}
+
+impl Annotation {
+    fn parse(text: &str) -> Self {
+        let text = text
+            .trim_start_matches([':', '：', '-', ' ', '　'])
+            .trim_end();
+        let digits = text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len());
+
+        if text.is_empty() {
+            return Self::Unspecified;
+        }
+        text[..digits]
+            .parse()
+            .map_or_else(|_| Self::Name(text.to_string()), Self::Id)
+    }
+
+    /// Describes how the annotation disagrees with the skill that is actually checked, if it does.
+    /// Names can only be compared when the database was read.
+    fn mismatch(&self, skills: &BTreeMap<u32, String>, checked: u32) -> Option<String> {
+        let annotated = match self {
+            Self::Unspecified => return None,
+            Self::Id(id) if *id == checked => return None,
+            Self::Id(id) => describe(skills, *id),
+            Self::Name(name)
+                if skills.is_empty()
+                    || skills
+                        .get(&checked)
+                        .is_some_and(|skill| mentions(name, skill)) =>
+            {
+                return None;
+            }
+            Self::Name(name) => name.clone(),
+        };
+        Some(format!(
+            "Annotated as checking {annotated}, but checks {}",
+            describe(skills, checked)
+        ))
+    }
+}
+
+/// Whether the annotation mentions the skill name, ignoring case, punctuation and spacing, so that
+/// `▽Skills (Flight)` or `▽Skills: Flight check` still count.
+fn mentions(annotation: &str, skill: &str) -> bool {
+    let normalize = |text: &str| {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let skill = normalize(skill);
+    !skill.is_empty() && normalize(annotation).contains(&skill)
+}
+
+fn describe(skills: &BTreeMap<u32, String>, id: u32) -> String {
+    skills.get(&id).filter(|name| !name.is_empty()).map_or_else(
+        || format!("skill {id}"),
+        |name| format!("skill {id} ({name})"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::Annotation;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Annotation::parse(""), Annotation::Unspecified);
+        assert_eq!(Annotation::parse(" ： "), Annotation::Unspecified);
+        assert_eq!(Annotation::parse(": 12"), Annotation::Id(12));
+        assert_eq!(Annotation::parse("12 (Flight)"), Annotation::Id(12));
+        assert_eq!(
+            Annotation::parse("：Flight "),
+            Annotation::Name(String::from("Flight"))
+        );
+        assert_eq!(
+            Annotation::parse("- (Flight)"),
+            Annotation::Name(String::from("(Flight)"))
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let skills = BTreeMap::from([(12, String::from("Flight")), (13, String::from("Bicycle"))]);
+        for annotation in ["Flight", "(flight)", "Flight check", "「Flight」"] {
+            assert_eq!(
+                Annotation::Name(String::from(annotation)).mismatch(&skills, 12),
+                None,
+                "{annotation}"
+            );
+        }
+        assert_eq!(
+            Annotation::Name(String::from("Bicycle")).mismatch(&skills, 12),
+            Some(String::from(
+                "Annotated as checking Bicycle, but checks skill 12 (Flight)"
+            ))
+        );
+        assert_eq!(
+            Annotation::Id(13).mismatch(&skills, 12),
+            Some(String::from(
+                "Annotated as checking skill 13 (Bicycle), but checks skill 12 (Flight)"
+            ))
+        );
+        assert_eq!(Annotation::Id(12).mismatch(&skills, 12), None);
+    }
+}
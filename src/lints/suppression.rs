@@ -0,0 +1,248 @@
+//! Inline suppression of diagnostics through event comments.
+//!
+//! A comment line of the form `@lint-allow <code>[, <code>...]` silences the named lints for the rest
+//! of the page it is on. `@lint-allow-event` extends that to every page of the event and
+//! `@lint-allow-map` to the whole map. Lints may be named by code or number; names of lints that do
+//! not exist are reported by `unknown-lint-allow`.
+
+use std::collections::{HashMap, HashSet};
+
+use lcf::raw::lmu::event::instruction::Instruction;
+
+use super::{Diagnostic, Lint};
+
+enum Scope {
+    Page,
+    Event,
+    Map,
+}
+
+#[derive(Default)]
+pub struct Suppressions {
+    map: HashSet<&'static str>,
+    events: HashMap<u32, HashSet<&'static str>>,
+    /// Keyed by event id and page id, holding the command id each suppression starts at.
+    pages: HashMap<(u32, u32), Vec<(u32, &'static str)>>,
+}
+
+impl Suppressions {
+    #[must_use]
+    pub fn new(map: &lcf::lmu::LcfMapUnit) -> Self {
+        let mut suppressions = Self::default();
+
+        for event in &map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    if !matches!(
+                        command.instruction,
+                        Instruction::Comment | Instruction::CommentNextLine
+                    ) {
+                        continue;
+                    }
+
+                    let comment = encoding_rs::SHIFT_JIS.decode(&command.string).0;
+                    let Some((scope, codes)) = parse(&comment) else {
+                        continue;
+                    };
+
+                    for code in codes.into_iter().filter_map(super::find).map(Lint::code) {
+                        match scope {
+                            Scope::Map => {
+                                suppressions.map.insert(code);
+                            }
+                            Scope::Event => {
+                                suppressions
+                                    .events
+                                    .entry(event.id)
+                                    .or_default()
+                                    .insert(code);
+                            }
+                            Scope::Page => suppressions
+                                .pages
+                                .entry((event.id, page_index as u32 + 1))
+                                .or_default()
+                                .push((command_index as u32 + 1, code)),
+                        }
+                    }
+                }
+            }
+        }
+
+        suppressions
+    }
+
+    #[must_use]
+    pub fn is_suppressed(&self, lint: &dyn Lint, diagnostic: &Diagnostic) -> bool {
+        let code = lint.code();
+        if self.map.contains(code) {
+            return true;
+        }
+
+        let Some(event) = &diagnostic.event else {
+            return false;
+        };
+
+        if self
+            .events
+            .get(&event.id.get())
+            .is_some_and(|codes| codes.contains(code))
+        {
+            return true;
+        }
+
+        let Some(page) = &event.page else {
+            return false;
+        };
+
+        self.pages
+            .get(&(event.id.get(), page.id.get()))
+            .is_some_and(|suppressions| {
+                suppressions.iter().any(|(start, suppressed)| {
+                    *suppressed == code
+                        && page.command.is_none_or(|command| command.get() >= *start)
+                })
+            })
+    }
+}
+
+/// The lints a comment names, if it is a suppression.
+fn parse(comment: &str) -> Option<(Scope, Vec<&str>)> {
+    let rest = comment.trim().strip_prefix("@lint-allow")?;
+    let (scope, rest) = [("-event", Scope::Event), ("-map", Scope::Map)]
+        .into_iter()
+        .find_map(|(suffix, scope)| rest.strip_prefix(suffix).map(|rest| (scope, rest)))
+        .unwrap_or((Scope::Page, rest));
+
+    // reject things like `@lint-allowed`
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let names = rest
+        .split(|char: char| char == ',' || char.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    Some((scope, names))
+}
+
+/// The names in a suppression comment that are no lint's code or number.
+#[must_use]
+pub fn unknown(comment: &str) -> Vec<&str> {
+    parse(comment)
+        .map(|(_, names)| names)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| super::find(name).is_none())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lcf::{enums::Trigger, raw::lmu::event::command::Command};
+
+    use super::*;
+    use crate::lints::{
+        lint_allow::LintAllowLint,
+        testing::{commands, comment, map, page, places, project, test},
+        v44_assignment::V44AssignmentLint,
+    };
+
+    fn assign() -> Vec<Command> {
+        commands(vec![Instruction::ControlVariables {
+            mode: 0,
+            start: 44,
+            end: 44,
+            operation: 0,
+            operand: 0,
+            value1: 0,
+            value2: 0,
+            unknown: None,
+        }])
+    }
+
+    /// Where the `v44-assignment` diagnostics that are not suppressed are.
+    fn unsuppressed(events: Vec<Vec<Vec<Command>>>) -> Vec<(u32, u32, u32)> {
+        let map = map(events
+            .into_iter()
+            .map(|pages| {
+                pages
+                    .into_iter()
+                    .map(|commands| page(Trigger::ActionButton, commands))
+                    .collect()
+            })
+            .collect());
+        let suppressions = Suppressions::new(&map);
+        let diagnostics = test(&V44AssignmentLint, &map, &project(Vec::new()))
+            .into_iter()
+            .filter(|diagnostic| !suppressions.is_suppressed(&V44AssignmentLint, diagnostic))
+            .collect::<Vec<_>>();
+        places(&diagnostics)
+    }
+
+    #[test]
+    fn page_from_comment_onward() {
+        let commands = [
+            assign(),
+            vec![comment("@lint-allow v44-assignment")],
+            assign(),
+        ]
+        .concat();
+        assert_eq!(unsuppressed(vec![vec![commands.clone()]]), [(1, 1, 1)]);
+        // other pages and events are not covered
+        assert_eq!(
+            unsuppressed(vec![vec![commands, assign()], vec![assign()]]),
+            [(1, 1, 1), (1, 2, 1), (2, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn event() {
+        let allow = [vec![comment("@lint-allow-event L0003")], assign()].concat();
+        assert_eq!(
+            unsuppressed(vec![vec![assign(), allow], vec![assign()]]),
+            [(2, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn map_by_number_and_code() {
+        for allow in [
+            "@lint-allow-map 3",
+            "@lint-allow-map L0003",
+            "@lint-allow-map l3",
+        ] {
+            let commands = [assign(), vec![comment(allow)]].concat();
+            assert!(
+                unsuppressed(vec![vec![assign()], vec![commands]]).is_empty(),
+                "{allow}"
+            );
+        }
+        // not a suppression at all
+        let allowed = [assign(), vec![comment("@lint-allowed 3")]].concat();
+        assert_eq!(unsuppressed(vec![vec![allowed]]), [(1, 1, 1)]);
+    }
+
+    #[test]
+    fn unknown_names() {
+        assert_eq!(
+            unknown("@lint-allow v44-assignment, L0099 prefer-move-pictures tissue-events"),
+            ["L0099", "prefer-move-pictures"]
+        );
+        assert!(unknown("@lint-allowed L0099").is_empty());
+
+        let map = map(vec![vec![page(
+            Trigger::ActionButton,
+            vec![
+                comment("@lint-allow-map 3"),
+                comment("@lint-allow-event 3,nonsense"),
+            ],
+        )]]);
+        let diagnostics = test(&LintAllowLint, &map, &project(Vec::new()));
+        assert_eq!(places(&diagnostics), [(1, 1, 2)]);
+        assert_eq!(
+            diagnostics[0].message.as_deref(),
+            Some("There is no lint `nonsense`, so nothing is suppressed")
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use lcf::raw::lmu::event::instruction::Instruction;
+
+use crate::project::{MapError, Project};
+
+pub struct TeleportLint;
+
+impl super::Lint for TeleportLint {
+    fn code(&self) -> &'static str {
+        "teleport-destination"
+    }
+
+    fn number(&self) -> usize {
+        11
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Teleport destinations must exist"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    let Some(destination) = Destination::of(&command.instruction) else {
+                        continue;
+                    };
+
+                    let problem = match destination.map {
+                        Some(map) => check(context.project, map, destination.x, destination.y),
+                        None if destination.x >= context.map.width
+                            || destination.y >= context.map.height =>
+                        {
+                            Some(format!(
+                                "Destination (X{:03}, Y{:03}) is outside of this map ({}x{})",
+                                destination.x, destination.y, context.map.width, context.map.height
+                            ))
+                        }
+                        None => None,
+                    };
+
+                    if let Some(message) = problem {
+                        diagnostics.push(super::Diagnostic {
+                            event: Some(super::DiagnosticEvent::from(event).with_page(
+                                super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                            )),
+                            level: super::DiagnosticLevel::Error,
+                            message: Some(message),
+                        });
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Where a command moves the player, a vehicle or an event to.
+pub struct Destination {
+    /// `None` for commands that always stay on the current map.
+    pub map: Option<u32>,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Destination {
+    /// The destination of a command, if it has one that is known without running the game.
+    #[must_use]
+    pub const fn of(instruction: &Instruction) -> Option<Self> {
+        match *instruction {
+            Instruction::TransferPlayer { map, x, y, .. }
+            | Instruction::SetVehicleLocation {
+                operand: 0,
+                map,
+                x,
+                y,
+                ..
+            } => Some(Self {
+                map: Some(map),
+                x,
+                y,
+            }),
+            Instruction::SetEventLocation {
+                mode: 0,
+                x_pos,
+                y_pos,
+                ..
+            } => Some(Self {
+                map: None,
+                x: x_pos,
+                y: y_pos,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Describes what is wrong with a position on another map, if anything.
+///
+/// Nothing can be said without a map tree, and maps that exist but cannot be parsed are already
+/// reported on their own.
+#[must_use]
+fn check(project: &Project, map: u32, x: u32, y: u32) -> Option<String> {
+    project.tree.as_ref()?;
+
+    let Some(summary) = u16::try_from(map).ok().and_then(|id| project.summary(id)) else {
+        return Some(format!("Destination Map{map:04} is not in the map tree"));
+    };
+
+    match summary {
+        Ok(summary) if !summary.contains(x, y) => Some(format!(
+            "Destination (X{x:03}, Y{y:03}) is outside of Map{map:04} ({}x{})",
+            summary.width, summary.height
+        )),
+        Ok(_) | Err(MapError::Invalid(_)) => None,
+        Err(MapError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Some(format!(
+            "Destination Map{map:04} is in the map tree, but Map{map:04}.lmu does not exist"
+        )),
+        Err(MapError::Io(err)) => Some(format!("Destination Map{map:04} could not be read: {err}")),
+    }
+}
@@ -0,0 +1,102 @@
+//! Small maps and projects built in code, for the tests of the lints.
+
+use lcf::{
+    enums::Trigger,
+    ldb::common_event::CommonEvent,
+    lmu::{
+        LcfMapUnit,
+        event::{Event, page::EventPage},
+    },
+    raw::lmu::event::{command::Command, instruction::Instruction},
+};
+
+use crate::project::Project;
+
+pub fn commands(instructions: Vec<Instruction>) -> Vec<Command> {
+    instructions
+        .into_iter()
+        .map(|instruction| Command {
+            indent: 0,
+            string: Vec::new(),
+            instruction,
+        })
+        .collect()
+}
+
+/// A comment line, encoded the way the editor saves it.
+pub fn comment(text: &str) -> Command {
+    Command {
+        indent: 0,
+        string: encoding_rs::SHIFT_JIS.encode(text).0.into_owned(),
+        instruction: Instruction::Comment,
+    }
+}
+
+pub fn page(trigger: Trigger, commands: Vec<Command>) -> EventPage {
+    EventPage {
+        trigger,
+        commands,
+        ..Default::default()
+    }
+}
+
+/// A map with one event for each list of pages, numbered from 1.
+pub fn map(events: Vec<Vec<EventPage>>) -> LcfMapUnit {
+    LcfMapUnit {
+        events: events
+            .into_iter()
+            .zip(1..)
+            .map(|(pages, id)| Event {
+                id,
+                pages,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// A project outside of any game folder, whose database only has the common events, numbered
+/// from 1.
+pub fn project(common_events: Vec<Vec<Command>>) -> Project {
+    let mut project = Project::new(std::path::PathBuf::from("/nonexistent"), None, None);
+    project.database = Some(lcf::ldb::LcfDataBase {
+        common_events: common_events
+            .into_iter()
+            .zip(1..)
+            .map(|(commands, id)| CommonEvent {
+                id,
+                commands,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    });
+    project
+}
+
+/// Runs a map lint on the map with the default settings.
+pub fn test(lint: &dyn super::Lint, map: &LcfMapUnit, project: &Project) -> Vec<super::Diagnostic> {
+    lint.test(&super::Context {
+        map_id: Some(1),
+        map,
+        project,
+        settings: &crate::config::Settings::default(),
+    })
+}
+
+/// Where the diagnostics are, as event id, page id and command id.
+pub fn places(diagnostics: &[super::Diagnostic]) -> Vec<(u32, u32, u32)> {
+    diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let event = diagnostic.event.as_ref()?;
+            let page = event.page.as_ref()?;
+            Some((
+                event.id.get(),
+                page.id.get(),
+                page.command.map_or(0, std::num::NonZeroU32::get),
+            ))
+        })
+        .collect()
+}
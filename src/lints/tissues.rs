@@ -3,11 +3,27 @@ use lcf::raw::lmu::event::instruction::Instruction;
 pub struct TissueLint;
 
 impl super::Lint for TissueLint {
+    fn code(&self) -> &'static str {
+        "tissue-events"
+    }
+
+    fn number(&self) -> usize {
+        2
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
     fn name(&self) -> &'static str {
         "Tissue event validity"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
         let main_sig = encoding_rs::SHIFT_JIS.encode("ティッシュ++++").0.to_vec();
         let helper_sigs = vec![
             "ティッシ", // carciniara beach (Map1024.lmu) uses ゥ instead of ュ
@@ -18,7 +34,12 @@ impl super::Lint for TissueLint {
         .map(|valid| encoding_rs::SHIFT_JIS.encode(valid).0.to_vec())
         .collect::<Vec<_>>();
 
-        let Some(tissue) = map.events.iter().find(|event| event.name == main_sig) else {
+        let Some(tissue) = context
+            .map
+            .events
+            .iter()
+            .find(|event| event.name == main_sig)
+        else {
             return super::Diagnostic {
                 event: None,
                 level: super::DiagnosticLevel::Warning,
@@ -58,7 +79,7 @@ impl super::Lint for TissueLint {
             .iter()
             .enumerate()
             .filter_map(|(index, id)| {
-                let Some(event) = map.events.iter().find(|event| event.id == *id) else {
+                let Some(event) = context.map.events.iter().find(|event| event.id == *id) else {
                     return Some(super::Diagnostic {
                         event: None,
                         level: super::DiagnosticLevel::Error,
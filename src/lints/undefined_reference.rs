@@ -0,0 +1,193 @@
+//! Pictures and charsets are files rather than database entries, so they are not checked here.
+//! Nothing is reported when the database could not be read.
+
+use std::collections::BTreeMap;
+
+use lcf::raw::lmu::event::instruction::Instruction;
+
+use crate::{project::Project, xref};
+
+pub struct UndefinedReferenceLint;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Switch,
+    Variable,
+    CommonEvent,
+    Item,
+    Actor,
+    Skill,
+}
+
+impl Entry {
+    fn describe(self, id: u32) -> String {
+        match self {
+            Self::Switch => format!("switch {}", xref::Kind::Switch.name(id)),
+            Self::Variable => format!("variable {}", xref::Kind::Variable.name(id)),
+            Self::CommonEvent => format!("common event {id}"),
+            Self::Item => format!("item {id}"),
+            Self::Actor => format!("actor {id}"),
+            Self::Skill => format!("skill {id}"),
+        }
+    }
+
+    fn exists(self, project: &Project, id: u32) -> bool {
+        // a table that could not be read says nothing about what is missing
+        let known = |names: &BTreeMap<u32, String>| names.is_empty() || names.contains_key(&id);
+        let names = &project.names;
+        match self {
+            Self::Switch => known(&names.switches),
+            Self::Variable => known(&names.variables),
+            Self::CommonEvent => project
+                .database
+                .iter()
+                .flat_map(|database| &database.common_events)
+                .any(|common_event| common_event.id == id),
+            Self::Item => known(&names.items),
+            Self::Actor => project
+                .database
+                .as_ref()
+                .is_some_and(|database| (1..=database.actors.len()).contains(&(id as usize))),
+            Self::Skill => known(&names.skills),
+        }
+    }
+}
+
+impl From<xref::Kind> for Entry {
+    fn from(kind: xref::Kind) -> Self {
+        match kind {
+            xref::Kind::Switch => Self::Switch,
+            xref::Kind::Variable => Self::Variable,
+        }
+    }
+}
+
+impl super::Lint for UndefinedReferenceLint {
+    fn code(&self) -> &'static str {
+        "undefined-reference"
+    }
+
+    fn number(&self) -> usize {
+        17
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "References should point to existing database entries"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let project = context.project;
+        if project.database.is_none() {
+            return Vec::new();
+        }
+        let mut diagnostics = Vec::new();
+
+        for event in &context.map.events {
+            for (page_index, page) in event.pages.iter().enumerate() {
+                let mut report = |command_index: Option<usize>, entry: Entry, id: u32| {
+                    let page = command_index.map_or_else(
+                        || super::DiagnosticPage::new_from_index(page_index),
+                        |command_index| {
+                            super::DiagnosticPage::new_from_indexes(page_index, command_index)
+                        },
+                    );
+                    diagnostics.push(super::Diagnostic {
+                        event: Some(super::DiagnosticEvent::from(event).with_page(page)),
+                        level: super::DiagnosticLevel::Error,
+                        message: Some(format!(
+                            "There is no {} in the database",
+                            entry.describe(id)
+                        )),
+                    });
+                };
+
+                let condition = &page.condition;
+                for (entry, (enabled, id)) in [
+                    (Entry::Switch, condition.switch_a),
+                    (Entry::Switch, condition.switch_b),
+                    (Entry::Variable, condition.variable),
+                    (Entry::Item, condition.item),
+                    (Entry::Actor, condition.actor),
+                ] {
+                    if enabled && !entry.exists(project, id) {
+                        report(None, entry, id);
+                    }
+                }
+
+                // batch changes are reported once, at the first id that is missing
+                let mut reported = Vec::new();
+                let mut found = |command_index, entry: Entry, id| {
+                    if !reported.contains(&(command_index, entry)) && !entry.exists(project, id) {
+                        reported.push((command_index, entry));
+                        report(Some(command_index), entry, id);
+                    }
+                };
+                xref::commands(&page.commands, |command_index, kind, id, _| {
+                    found(command_index, kind.into(), id);
+                });
+                for (command_index, command) in page.commands.iter().enumerate() {
+                    for (entry, id) in entries(&command.instruction) {
+                        found(command_index, entry, id);
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// The database entries other than switches and variables that an instruction picks by a
+/// constant id.
+fn entries(instruction: &Instruction) -> Vec<(Entry, u32)> {
+    match *instruction {
+        Instruction::CallEvent { mode: 0, index, .. } => vec![(Entry::CommonEvent, index)],
+        Instruction::ChangeItems {
+            item_target: 0,
+            item,
+            ..
+        } => vec![(Entry::Item, item)],
+        Instruction::ChangePartyMembers {
+            operand: 0, actor, ..
+        } => vec![(Entry::Actor, actor)],
+        Instruction::ChangeSkills {
+            actor_operand,
+            actor,
+            operand,
+            value,
+            ..
+        } => {
+            let mut entries = Vec::new();
+            if actor_operand == 1 {
+                entries.push((Entry::Actor, actor));
+            }
+            if operand == 0 {
+                entries.push((Entry::Skill, value));
+            }
+            entries
+        }
+        Instruction::ConditionalBranch {
+            mode,
+            field1,
+            field2,
+            field3,
+            ..
+        } => match (mode, field2) {
+            (4, _) => vec![(Entry::Item, field1)],
+            // knowing a skill, and having an item equipped
+            (5, 4) => vec![(Entry::Actor, field1), (Entry::Skill, field3)],
+            (5, 5) => vec![(Entry::Actor, field1), (Entry::Item, field3)],
+            (5, _) => vec![(Entry::Actor, field1)],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{graph::Node, project::Project};
+
+pub struct UnreachableLint;
+
+impl super::Lint for UnreachableLint {
+    fn code(&self) -> &'static str {
+        "unreachable-map"
+    }
+
+    fn number(&self) -> usize {
+        13
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
+    fn name(&self) -> &'static str {
+        "Maps must be reachable from the start map"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn scope(&self) -> super::Scope {
+        super::Scope::Project
+    }
+
+    fn test_project(
+        &self,
+        project: &Project,
+        settings: &crate::config::Settings,
+    ) -> Vec<super::Diagnostic> {
+        let Some(start) = settings.start_map.or_else(|| {
+            let party = project.tree.as_ref()?.start.party.as_ref()?;
+            u16::try_from(party.map).ok()
+        }) else {
+            return Vec::new();
+        };
+
+        let graph = project.graph();
+        // common events that run on their own can move the player from any map
+        let automatic = graph
+            .nodes
+            .iter()
+            .filter(|info| info.automatic)
+            .map(|info| info.node);
+        let reachable = graph.reachable(std::iter::once(Node::Map(start)).chain(automatic));
+
+        graph
+            .nodes
+            .iter()
+            .filter(|info| matches!(info.node, Node::Map(_)) && !reachable.contains(&info.node))
+            .map(|info| super::Diagnostic {
+                event: None,
+                level: super::DiagnosticLevel::Warning,
+                message: Some(format!(
+                    "{} ({}) cannot be reached from Map{start:04}",
+                    info.node, info.name
+                )),
+            })
+            .collect()
+    }
+}
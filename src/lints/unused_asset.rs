@@ -0,0 +1,87 @@
+//! A file counts as used when a map or the database refers to it under any extension the engine
+//! tries, since which one gets loaded depends on what else is in the folder.
+
+use std::collections::HashSet;
+
+use crate::{
+    assets::{self, Kind},
+    project::{MapError, Project},
+};
+
+pub struct UnusedAssetLint;
+
+impl super::Lint for UnusedAssetLint {
+    fn code(&self) -> &'static str {
+        "unused-asset"
+    }
+
+    fn number(&self) -> usize {
+        19
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Style
+    }
+
+    fn name(&self) -> &'static str {
+        "Files in the game folder should be used"
+    }
+
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Warning
+    }
+
+    fn scope(&self) -> super::Scope {
+        super::Scope::Project
+    }
+
+    fn test_project(
+        &self,
+        project: &Project,
+        settings: &crate::config::Settings,
+    ) -> Vec<super::Diagnostic> {
+        let mut used = HashSet::new();
+        let mut add = |asset: &assets::Asset| {
+            let name = encoding_rs::SHIFT_JIS.decode(&asset.name).0.to_lowercase();
+            used.insert((asset.kind, name));
+        };
+
+        if let Some(database) = &project.database {
+            assets::of_database(database).iter().for_each(&mut add);
+        }
+        let maps = project
+            .tree
+            .iter()
+            .flat_map(|tree| &tree.maps)
+            .filter(|(_, map)| matches!(map.r#type, lcf::lmt::MapType::Map));
+        for (id, _) in maps {
+            match project.summary(*id) {
+                Some(Ok(summary)) => summary.assets.iter().for_each(&mut add),
+                // a map without a file uses nothing
+                Some(Err(MapError::Io(err))) if err.kind() == std::io::ErrorKind::NotFound => (),
+                // but whatever an unreadable map uses is unknown, which would make anything look
+                // unused
+                _ => return Vec::new(),
+            }
+        }
+
+        let files = project.files(&settings.rtp);
+        Kind::ALL
+            .into_iter()
+            .flat_map(|kind| files.in_game(kind).map(move |file| (kind, file)))
+            .filter(|(kind, file)| {
+                let (stem, extension) = file.rsplit_once('.').unwrap_or((file, ""));
+                // files the engine never loads are none of this lint's business
+                kind.extensions()
+                    .iter()
+                    .any(|known| extension.eq_ignore_ascii_case(known))
+                    && !used.contains(&(*kind, stem.to_lowercase()))
+            })
+            .map(|(kind, file)| super::Diagnostic {
+                event: None,
+                level: super::DiagnosticLevel::Warning,
+                message: Some(format!("{}/{file} is not used", kind.dir())),
+            })
+            .collect()
+    }
+}
@@ -1,32 +1,51 @@
-use lcf::raw::lmu::event::instruction::Instruction;
+//! Also see `range-ownership`, which covers any number of switches and variables.
+
+use crate::xref::{self, Access, Kind};
 
 pub struct V44AssignmentLint;
 
 impl super::Lint for V44AssignmentLint {
+    fn code(&self) -> &'static str {
+        "v44-assignment"
+    }
+
+    fn number(&self) -> usize {
+        3
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
     fn name(&self) -> &'static str {
         "V0044 should not be assigned to"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let variable = context.settings.read_only_variable;
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
-                for (command_index, command) in page.commands.iter().enumerate() {
-                    if let Instruction::ControlVariables {
-                        mode, start, end, ..
-                    } = command.instruction
-                        && ((mode == 0 && start == 44) || (mode == 1 && start <= 44 && 44 <= end))
-                    {
-                        diagnostics.push(super::Diagnostic {
-                            event: Some(super::DiagnosticEvent::from(event).with_page(
-                                super::DiagnosticPage::new_from_indexes(page_index, command_index),
-                            )),
-                            level: super::DiagnosticLevel::Error,
-                            message: None,
-                        });
+                let mut written = Vec::new();
+                xref::commands(&page.commands, |command_index, kind, id, access| {
+                    if kind == Kind::Variable && id == variable && access == Access::Write {
+                        written.push(command_index);
                     }
-                }
+                });
+                written.dedup();
+
+                diagnostics.extend(written.into_iter().map(|command_index| super::Diagnostic {
+                    event: Some(super::DiagnosticEvent::from(event).with_page(
+                        super::DiagnosticPage::new_from_indexes(page_index, command_index),
+                    )),
+                    level: super::DiagnosticLevel::Error,
+                    message: None,
+                }));
             }
         }
 
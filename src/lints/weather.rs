@@ -1,74 +1,216 @@
+//! Each page is checked on its own, and every change has to be paired up on every way through the
+//! page that it is on.
+
 use lcf::raw::lmu::event::instruction::Instruction;
 
+use super::flow::Flow;
+
 pub struct WeatherLint;
 
-enum State {
-    Normal,
-    ExpectingWeather,
-    ExpectingVariable,
-    Finished,
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Expecting {
+    Weather,
+    Variable,
+}
+
+/// A change that has not been paired up yet, and the index of its instruction.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Pending {
+    expecting: Expecting,
+    index: usize,
 }
 
 impl super::Lint for WeatherLint {
+    fn code(&self) -> &'static str {
+        "weather-v42-parity"
+    }
+
+    fn number(&self) -> usize {
+        1
+    }
+
+    fn category(&self) -> super::Category {
+        super::Category::Correctness
+    }
+
     fn name(&self) -> &'static str {
         "Parity between weather and V0042"
     }
 
-    fn test(&self, map: &lcf::lmu::LcfMapUnit) -> Vec<super::Diagnostic> {
-        let mut state = State::Normal;
+    fn default_level(&self) -> super::DiagnosticLevel {
+        super::DiagnosticLevel::Error
+    }
+
+    fn test(&self, context: &super::Context) -> Vec<super::Diagnostic> {
+        let variable = context.settings.weather_variable;
         let mut diagnostics = Vec::new();
 
-        for event in &map.events {
+        for event in &context.map.events {
             for (page_index, page) in event.pages.iter().enumerate() {
-                for command in &page.commands {
-                    match command.instruction {
-                        Instruction::WeatherEffects { .. } => {
-                            state = match state {
-                                State::Normal => State::ExpectingVariable,
-                                State::ExpectingWeather => State::Finished,
-                                x => x,
-                            }
-                        }
-                        Instruction::ControlVariables {
-                            mode, start, end, ..
-                        } => {
-                            if match mode {
-                                0 => start == 42,
-                                1 => start <= 42 && 42 <= end,
-                                _ => false,
-                            } {
-                                state = match state {
-                                    State::Normal => State::ExpectingWeather,
-                                    State::ExpectingVariable => State::Finished,
-                                    x => x,
-                                }
-                            }
-                        }
-                        _ => (),
-                    }
-                }
+                let flow = Flow::new(&page.commands);
+                let states = flow.states(None, |index, command, pending| {
+                    vec![step(variable, index, &command.instruction, pending)]
+                });
+                let mut unpaired = states[flow.end()].iter().flatten().collect::<Vec<_>>();
+                unpaired.sort_by_key(|pending| pending.index);
 
-                // todo: find index of last command
-                diagnostics.push(super::Diagnostic {
+                diagnostics.extend(unpaired.into_iter().map(|pending| super::Diagnostic {
                     event: Some(super::DiagnosticEvent::from(event).with_page(
-                        super::DiagnosticPage::new_from_index(page_index),
+                        super::DiagnosticPage::new_from_indexes(page_index, pending.index),
                     )),
                     level: super::DiagnosticLevel::Error,
-                    message: Some(match state {
-                        State::ExpectingVariable => {
-                            state = State::Normal;
-                            "V0042 is not changed after changing the weather.".to_string()
+                    message: Some(match pending.expecting {
+                        Expecting::Variable => {
+                            format!("V{variable:04} is not changed after changing the weather.")
                         }
-                        State::ExpectingWeather => {
-                            state = State::Normal;
-                            "The weather is not changed after changing V0042.".to_string()
+                        Expecting::Weather => {
+                            format!("The weather is not changed after changing V{variable:04}.")
                         }
-                        _ => continue,
                     }),
-                });
+                }));
             }
         }
 
         diagnostics
     }
 }
+
+/// What is pending after an instruction.
+const fn step(
+    variable: u32,
+    index: usize,
+    instruction: &Instruction,
+    pending: Option<Pending>,
+) -> Option<Pending> {
+    match *instruction {
+        Instruction::WeatherEffects { .. } => match pending {
+            None => Some(Pending {
+                expecting: Expecting::Variable,
+                index,
+            }),
+            Some(Pending {
+                expecting: Expecting::Weather,
+                ..
+            }) => None,
+            other => other,
+        },
+        Instruction::ControlVariables {
+            mode, start, end, ..
+        } => {
+            let changed = match mode {
+                0 => start == variable,
+                1 => start <= variable && variable <= end,
+                _ => false,
+            };
+            match pending {
+                None if changed => Some(Pending {
+                    expecting: Expecting::Weather,
+                    index,
+                }),
+                // a variable picked through another variable may well be the weather one, which is
+                // enough to pair up a weather change, but not to expect one
+                Some(Pending {
+                    expecting: Expecting::Variable,
+                    ..
+                }) if changed || mode == 2 => None,
+                other => other,
+            }
+        }
+        _ => pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lcf::enums::Trigger;
+
+    use super::*;
+    use crate::lints::testing::{commands, map, page, places, project, test};
+
+    const WEATHER: Instruction = Instruction::WeatherEffects {
+        r#type: 1,
+        power: 0,
+    };
+
+    const fn set(mode: u32, start: u32) -> Instruction {
+        Instruction::ControlVariables {
+            mode,
+            start,
+            end: start,
+            operation: 0,
+            operand: 0,
+            value1: 0,
+            value2: 0,
+            unknown: None,
+        }
+    }
+
+    const fn branch() -> Instruction {
+        Instruction::ConditionalBranch {
+            mode: 0,
+            field1: 1,
+            field2: 0,
+            field3: 0,
+            field4: 0,
+            has_else: 1,
+        }
+    }
+
+    fn check(instructions: Vec<Instruction>) -> Vec<u32> {
+        let map = map(vec![vec![page(
+            Trigger::ActionButton,
+            commands(instructions),
+        )]]);
+        places(&test(&WeatherLint, &map, &project(Vec::new())))
+            .into_iter()
+            .map(|(_, _, command)| command)
+            .collect()
+    }
+
+    #[test]
+    fn either_order() {
+        assert!(check(vec![WEATHER, set(0, 42)]).is_empty());
+        assert!(check(vec![set(0, 42), WEATHER]).is_empty());
+        assert_eq!(check(vec![set(0, 42)]), [1]);
+        assert_eq!(check(vec![WEATHER, set(0, 43)]), [1]);
+    }
+
+    #[test]
+    fn split_across_branch() {
+        // paired up in both arms
+        assert!(
+            check(vec![
+                WEATHER,
+                branch(),
+                set(0, 42),
+                Instruction::End,
+                Instruction::ElseBranch,
+                set(0, 42),
+                Instruction::End,
+                Instruction::EndBranch { unknown: None },
+            ])
+            .is_empty()
+        );
+        // the else arm leaves the weather change unpaired
+        assert_eq!(
+            check(vec![
+                WEATHER,
+                branch(),
+                set(0, 42),
+                Instruction::End,
+                Instruction::ElseBranch,
+                Instruction::End,
+                Instruction::EndBranch { unknown: None },
+            ]),
+            [1]
+        );
+    }
+
+    #[test]
+    fn variable_picked_through_variable() {
+        // may pair up a weather change, but does not start one
+        assert!(check(vec![WEATHER, set(2, 10)]).is_empty());
+        assert!(check(vec![set(2, 10)]).is_empty());
+        assert_eq!(check(vec![set(2, 10), WEATHER]), [2]);
+    }
+}
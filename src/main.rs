@@ -4,179 +4,491 @@
 #![allow(clippy::missing_panics_doc)]
 
 use indicatif::ParallelProgressIterator as _;
-use lcf::ConvertExt;
 pub use lints::{Diagnostic, DiagnosticEvent, DiagnosticLevel, DiagnosticPage, Lint};
-use owo_colors::OwoColorize;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
+mod assets;
+mod baseline;
+mod config;
+mod graph;
 mod lints;
+mod output;
+mod project;
+mod xref;
 
-#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     All,
     Warn,
     Error,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    Warn,
+    Error,
+    Never,
+}
+
+/// Outcome of a run, reported as the process exit code. Ordered by severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Clean = 0,
+    Warnings = 1,
+    Errors = 2,
+    Unreadable = 3,
+}
+
+impl Status {
+    fn of(report: &output::Report) -> Self {
+        let unreadable = (!report.errors.is_empty()).then_some(Self::Unreadable);
+        report
+            .maps
+            .iter()
+            .map(|map| {
+                map.result
+                    .as_deref()
+                    .map_or(Self::Unreadable, Self::of_lints)
+            })
+            .chain(std::iter::once(Self::of_lints(&report.project)))
+            .chain(unreadable)
+            .max()
+            .unwrap_or(Self::Clean)
+    }
+
+    fn of_lints(lints: &[output::LintReport]) -> Self {
+        lints
+            .iter()
+            .flat_map(|lint| &lint.diagnostics)
+            .map(|diagnostic| match diagnostic.level {
+                DiagnosticLevel::Warning => Self::Warnings,
+                DiagnosticLevel::Error => Self::Errors,
+            })
+            .max()
+            .unwrap_or(Self::Clean)
+    }
+
+    const fn exit_code(self, fail_on: &FailOn) -> i32 {
+        match (fail_on, self) {
+            (FailOn::Never, _) | (FailOn::Error, Self::Warnings) => Self::Clean as i32,
+            _ => self as i32,
+        }
+    }
+}
+
 #[derive(clap::Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(index = 1, default_value = ".")]
     path: std::path::PathBuf,
-    /// Minimum level for logging, everything lower will be ignored.
-    #[arg(long, default_value = "all")]
+    /// Minimum level for logging, everything lower will be ignored. [default: all]
+    #[arg(long)]
+    level: Option<LogLevel>,
+    /// Ignored lints, by code (`weather-v42-parity`) or number (`L0001`, `1`).
+    #[arg(long, value_delimiter = ',', value_parser = parse_lint)]
+    ignore: Vec<&'static str>,
+    /// Format of the report written to stdout.
+    #[arg(long, default_value = "text")]
+    format: output::Format,
+    /// Lowest severity that results in a non-zero exit code.
+    ///
+    /// Exit codes: 0 = clean, 1 = warnings, 2 = errors, 3 = unreadable input. [default: error]
+    #[arg(long)]
+    fail_on: Option<FailOn>,
+    /// Also report diagnostics silenced by `@lint-allow` comments, marked as suppressed.
+    #[arg(long)]
+    show_suppressed: bool,
+    /// Hide diagnostics already recorded in this baseline file.
+    #[arg(long)]
+    baseline: Option<std::path::PathBuf>,
+    /// Record every current diagnostic into this baseline file.
+    #[arg(long)]
+    write_baseline: Option<std::path::PathBuf>,
+    /// Map tree to use instead of the `RPG_RT.lmt` in the game folder.
+    #[arg(long)]
+    tree: Option<std::path::PathBuf>,
+    /// Database to use instead of the `RPG_RT.ldb` in the game folder.
+    #[arg(long)]
+    database: Option<std::path::PathBuf>,
+    /// Configuration file to use instead of the `.lcf-validate.toml` in the game folder.
+    #[arg(long)]
+    config: Option<std::path::PathBuf>,
+    /// Write the transfers between maps to this file, as a graph in `--graph-format`.
+    #[arg(long)]
+    graph: Option<std::path::PathBuf>,
+    /// Format of the graph written by `--graph`.
+    #[arg(long, default_value = "dot")]
+    graph_format: graph::Format,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// List where every switch and variable is read or written.
+    Xref(xref::Args),
+}
+
+/// Everything deciding which diagnostics are reported, merged from the arguments and the config file.
+struct Options {
     level: LogLevel,
-    /// Ignored lints
-    #[arg(long, value_delimiter = ',')]
-    ignore: Vec<usize>,
+    ignored: Vec<&'static str>,
+    show_suppressed: bool,
+    baseline: Option<baseline::Baseline>,
+    write_baseline: bool,
+    config: config::Config,
+}
+
+impl Options {
+    fn new(args: &Args, config: config::Config) -> Self {
+        Self {
+            level: args
+                .level
+                .clone()
+                .or_else(|| config.level.clone())
+                .unwrap_or(LogLevel::All),
+            ignored: args.ignore.clone(),
+            show_suppressed: args.show_suppressed,
+            baseline: args.baseline.as_deref().map(|path| {
+                baseline::Baseline::load(path).unwrap_or_else(|err| {
+                    eprintln!("Invalid baseline: {err}");
+                    exit(Status::Unreadable as i32);
+                })
+            }),
+            write_baseline: args.write_baseline.is_some(),
+            config,
+        }
+    }
 }
 
 fn main() {
     let args = <Args as clap::Parser>::parse();
 
-    let target = if std::fs::metadata(&args.path).unwrap().is_dir() {
-        let tree = read_tree(&args.path);
-        either::Either::Right((args.path, tree))
-    } else {
-        match args.path.extension().and_then(std::ffi::OsStr::to_str) {
-            Some("lmt" | "ldb") => either::Either::Right((
-                args.path.parent().unwrap().to_owned(),
-                read_tree(&args.path.with_extension("lmt")),
-            )),
-            Some("lmu") => either::Either::Left(args.path),
-            x => {
-                println!(
-                    "Unrecognized extension {} is not supported.",
-                    x.unwrap_or("<none>")
-                );
-                exit();
-            }
+    if let Some(Command::Xref(xref)) = &args.command {
+        if let Err(err) = xref::run(xref) {
+            println!("Could not list references: {err}");
+            exit(Status::Unreadable as i32);
         }
-    };
+        exit(Status::Clean as i32);
+    }
 
-    match target {
-        either::Either::Left(single) => analyze(
-            read_map(&single).unwrap().unwrap(),
-            &args.level,
-            &args.ignore,
+    let mut errors = Vec::new();
+    let (single, ids, project) = open(&args, &mut errors);
+    let config = args
+        .config
+        .as_deref()
+        .map_or_else(
+            || config::Config::discover(&project.dir),
+            config::Config::load,
         )
-        .for_each(|line| println!("{}", line)),
-        either::Either::Right((base, tree)) => {
-            let mut maps = tree.maps[1..]
-                .into_par_iter()
-                .progress_with(
-                    indicatif::ProgressBar::new(tree.maps.len() as u64 - 1).with_style(
-                        indicatif::ProgressStyle::default_bar()
-                            .template(
-                                "[{elapsed} / {duration}] {bar:40.cyan/blue} {pos:>4}/{len:4} ({percent}%) {per_sec:>0}",
-                            )
-                            .unwrap(),
-                    ),
-                )
-                .map(
-                    |(id, _)| match read_map(&base.join(format!("Map{id:04}.lmu"))) {
-                        Ok(Ok(map)) => (
-                            id,
-                            either::Right(
-                                analyze(map, &args.level, &args.ignore).collect::<Vec<_>>(),
-                            ),
-                        ),
-                        Ok(Err(err)) => (
-                            id,
-                            either::Left(format!(
-                                "Map{id:04}.lmu\n  {}: {}",
-                                "Invalid map file".on_red(),
-                                err.red()
-                            )),
-                        ),
-                        Err(err) => (id, either::Left(format!("Map{id:04}.lmu\n  {}", err.red()))),
-                    },
-                )
-                .collect::<Vec<_>>();
-            maps.sort_by_key(|item| *item.0);
-            maps.iter().for_each(|(id, result)| match result {
-                either::Left(err) => println!("{}", err),
-                either::Right(output) => {
-                    println!("Map{id:04}.lmu:");
-                    for line in output {
-                        println!("  {}", line);
-                    }
-                }
-            });
+        .unwrap_or_else(|err| {
+            eprintln!("Invalid configuration: {err}");
+            exit(Status::Unreadable as i32);
+        });
+    let fail_on = args
+        .fail_on
+        .clone()
+        .or_else(|| config.fail_on.clone())
+        .unwrap_or(FailOn::Error);
+    let options = Options::new(&args, config);
+
+    let (mut reports, mut entries) = single.as_deref().map_or_else(
+        || analyze_tree(ids, &project, &options),
+        |single| {
+            let (report, entries) = analyze_file(single, None, &project, &options);
+            (vec![report], vec![entries])
+        },
+    );
+    reports.sort_by_key(|report| report.id);
+
+    // project lints need the whole game folder
+    let project_reports = if single.is_none() {
+        let (project_reports, project_entries) = analyze_project(&project, &options);
+        entries.push(project_entries);
+        project_reports
+    } else {
+        Vec::new()
+    };
+
+    if let Some(path) = &args.write_baseline {
+        let mut entries = entries.into_iter().flatten().collect::<Vec<_>>();
+        entries.sort_by_key(baseline::Entry::map);
+        if let Err(err) = baseline::Baseline::write(path, entries) {
+            eprintln!("Could not write baseline: {err}");
+            exit(Status::Unreadable as i32);
         }
+    }
+
+    if let Some(path) = &args.graph
+        && let Err(err) = write_graph(path, &args.graph_format, &project)
+    {
+        println!("Could not write graph: {err}");
+        exit(Status::Unreadable as i32);
+    }
+
+    let report = output::Report {
+        errors,
+        project: project_reports,
+        maps: reports,
+        single: single.is_some(),
     };
+    output::write(&args.format, &report);
+
+    exit(Status::of(&report).exit_code(&fail_on));
+}
+
+fn write_graph(
+    path: &std::path::Path,
+    format: &graph::Format,
+    project: &project::Project,
+) -> std::io::Result<()> {
+    if project.tree.is_none() {
+        return Err(std::io::Error::other("there is no map tree"));
+    }
 
-    exit();
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    project.graph().write(format, &mut out)?;
+    std::io::Write::flush(&mut out)
 }
 
-fn read_tree(path: &std::path::Path) -> lcf::lmt::LcfMapTree {
-    let bytes = std::fs::read(path.join("RPG_RT.lmt")).unwrap();
-    lcf::lmt::LcfMapTree::read(&mut std::io::Cursor::new(bytes)).unwrap()
+fn parse_lint(name: &str) -> Result<&'static str, String> {
+    lints::find(name)
+        .map(Lint::code)
+        .ok_or_else(|| format!("unknown lint `{name}`"))
 }
 
-fn analyze(
-    map: lcf::lmu::LcfMapUnit,
-    level: &LogLevel,
-    ignored: &[usize],
-) -> impl Iterator<Item = String> {
-    lints::ALL
-        .iter()
-        .enumerate()
-        .filter(|(index, _)| !ignored.iter().any(|ignore| *ignore == index + 1))
-        .map(move |(index, lint)| (index + 1, lint.name(), lint.test(&map)))
-        .filter_map(move |(index, name, diagnostics)| match level {
-            LogLevel::All => Some((index, name, diagnostics)),
-            LogLevel::Warn => {
-                if diagnostics.is_empty() {
-                    None
-                } else {
-                    Some((index, name, diagnostics))
-                }
-            }
-            LogLevel::Error => {
-                let diagnostics = diagnostics
-                    .into_iter()
-                    .filter(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Error))
-                    .collect::<Vec<_>>();
-                if diagnostics.is_empty() {
-                    None
-                } else {
-                    Some((index, name, diagnostics))
-                }
+/// Finds the maps to check from the path given, recording what could not be read on the way.
+fn open(
+    args: &Args,
+    errors: &mut Vec<project::ProjectError>,
+) -> (Option<std::path::PathBuf>, Vec<u16>, project::Project) {
+    let (single, project) = project::open(
+        &args.path,
+        args.tree.as_deref(),
+        args.database.as_deref(),
+        errors,
+    )
+    .unwrap_or_else(|err| fail(args, err));
+    let ids = match (&single, &project.tree) {
+        (Some(_), _) => Vec::new(),
+        // areas have no file, while a missing file is unreadable input whatever lints are on
+        (None, Some(tree)) => tree
+            .maps
+            .iter()
+            .filter(|(_, map)| matches!(map.r#type, lcf::lmt::MapType::Map))
+            .map(|(id, _)| *id)
+            .collect(),
+        // the maps can still be checked on their own
+        (None, None) => project.map_files.keys().copied().collect(),
+    };
+    (single, ids, project)
+}
+
+/// Reports a path that cannot be checked at all, and exits.
+fn fail(args: &Args, err: project::ProjectError) -> ! {
+    let report = output::Report {
+        errors: vec![err],
+        project: Vec::new(),
+        maps: Vec::new(),
+        single: false,
+    };
+    output::write(&args.format, &report);
+    exit(Status::Unreadable as i32);
+}
+
+/// Analyzes every map of the tree in parallel, showing the progress.
+fn analyze_tree(
+    ids: Vec<u16>,
+    project: &project::Project,
+    options: &Options,
+) -> (Vec<output::MapReport>, Vec<Vec<baseline::Entry>>) {
+    let progress = indicatif::ProgressBar::new(ids.len() as u64).with_style(
+        indicatif::ProgressStyle::default_bar()
+            .template(
+                "[{elapsed} / {duration}] {bar:40.cyan/blue} {pos:>4}/{len:4} ({percent}%) {per_sec:>0}",
+            )
+            .unwrap(),
+    );
+
+    ids.into_par_iter()
+        .progress_with(progress)
+        .map(|id| analyze_file(&project.map_path(id), Some(id), project, options))
+        .unzip()
+}
+
+/// Analyzes a single map, also returning its baseline entries if they are going to be written.
+fn analyze_file(
+    path: &std::path::Path,
+    id: Option<u16>,
+    project: &project::Project,
+    options: &Options,
+) -> (output::MapReport, Vec<baseline::Entry>) {
+    let file = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let id = id.or_else(|| project::map_id(&file));
+
+    let (result, entries) = match project::read_map(path) {
+        Ok(map) => {
+            if let Some(id) = id {
+                project.remember(id, &map);
             }
+
+            let context = lints::Context {
+                map_id: id,
+                map: &map,
+                project,
+                settings: &options.config.settings,
+            };
+            let (reports, entries) = analyze(&context, options);
+            (Ok(reports), entries)
+        }
+        Err(err) => (Err(err), Vec::new()),
+    };
+
+    (output::MapReport { id, file, result }, entries)
+}
+
+fn analyze(
+    context: &lints::Context,
+    options: &Options,
+) -> (Vec<output::LintReport>, Vec<baseline::Entry>) {
+    let suppressions = lints::suppression::Suppressions::new(context.map);
+    let mut known = Known::new(options, context.map_id);
+
+    let reports = selected(options, lints::Scope::Map)
+        .filter_map(|lint| {
+            let (suppressed, diagnostics) = lint
+                .test(context)
+                .into_iter()
+                .partition::<Vec<_>, _>(|diagnostic| suppressions.is_suppressed(lint, diagnostic));
+
+            report(lint, diagnostics, suppressed, options, |diagnostic| {
+                known.check(|| baseline::Entry::new(context.map_id, context.map, lint, diagnostic))
+            })
         })
-        .flat_map(|(index, name, diagnostics)| {
-            if diagnostics.is_empty() {
-                vec![format!("L{index:04}: {}", name.green())]
-            } else {
-                let mut items = Vec::with_capacity(diagnostics.len() + 1);
-                items.push(format!("L{index:04}: {name}:"));
-                items.extend(
-                    diagnostics
-                        .into_iter()
-                        .map(|diagnostic| match diagnostic.level {
-                            DiagnosticLevel::Warning => format!("  {}", diagnostic.yellow()),
-                            DiagnosticLevel::Error => format!("  {}", diagnostic.red()),
-                        }),
-                );
-                items
-            }
+        .collect();
+
+    (reports, known.entries)
+}
+
+fn analyze_project(
+    project: &project::Project,
+    options: &Options,
+) -> (Vec<output::LintReport>, Vec<baseline::Entry>) {
+    let mut known = Known::new(options, None);
+
+    let reports = selected(options, lints::Scope::Project)
+        .filter_map(|lint| {
+            let diagnostics = lint.test_project(project, &options.config.settings);
+            report(lint, diagnostics, Vec::new(), options, |diagnostic| {
+                known.check(|| baseline::Entry::project(lint, diagnostic))
+            })
         })
+        .collect();
+
+    (reports, known.entries)
 }
 
-fn read_map(
-    path: &std::path::Path,
-) -> Result<Result<lcf::lmu::LcfMapUnit, lcf::lmu::LcfMapUnitReadError>, std::io::Error> {
-    let bytes = std::fs::read(path)?;
-    let mut cursor = std::io::Cursor::new(bytes);
-    let map = lcf::lmu::LcfMapUnit::read(&mut cursor);
-    Ok(map)
+/// The lints of the given scope that are neither ignored nor turned off.
+fn selected(options: &Options, scope: lints::Scope) -> impl Iterator<Item = &'static dyn Lint> {
+    lints::ALL.iter().copied().filter(move |lint| {
+        lint.scope() == scope
+            && !options.ignored.contains(&lint.code())
+            && options.config.is_enabled(*lint)
+    })
+}
+
+/// Baseline bookkeeping for the diagnostics of one map, or of the project lints.
+struct Known<'a> {
+    options: &'a Options,
+    remaining: std::collections::HashMap<baseline::Entry, usize>,
+    entries: Vec<baseline::Entry>,
+}
+
+impl<'a> Known<'a> {
+    fn new(options: &'a Options, map: Option<u16>) -> Self {
+        Self {
+            options,
+            remaining: options
+                .baseline
+                .as_ref()
+                .map(|baseline| baseline.for_map(map))
+                .unwrap_or_default(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Whether the diagnostic the entry is made for was already recorded in the baseline.
+    fn check(&mut self, entry: impl FnOnce() -> baseline::Entry) -> bool {
+        if !self.options.write_baseline && self.options.baseline.is_none() {
+            return false;
+        }
+
+        let entry = entry();
+        let known = baseline::consume(&mut self.remaining, &entry);
+        if self.options.write_baseline {
+            self.entries.push(entry);
+        }
+        known
+    }
+}
+
+/// Applies the configured level, the baseline and the log level to the diagnostics of a lint.
+fn report(
+    lint: &'static dyn Lint,
+    mut diagnostics: Vec<Diagnostic>,
+    mut suppressed: Vec<Diagnostic>,
+    options: &Options,
+    mut is_known: impl FnMut(&Diagnostic) -> bool,
+) -> Option<output::LintReport> {
+    if !options.show_suppressed {
+        suppressed.clear();
+    }
+    if let Some(level) = options.config.level(lint) {
+        for diagnostic in diagnostics.iter_mut().chain(&mut suppressed) {
+            diagnostic.level = level;
+        }
+    }
+    diagnostics.retain(|diagnostic| !is_known(diagnostic));
+
+    let mut report = output::LintReport {
+        lint,
+        diagnostics,
+        suppressed,
+    };
+    match options.level {
+        LogLevel::All => Some(report),
+        LogLevel::Warn => {
+            if report.is_empty() {
+                None
+            } else {
+                Some(report)
+            }
+        }
+        LogLevel::Error => {
+            report
+                .diagnostics
+                .retain(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Error));
+            report
+                .suppressed
+                .retain(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Error));
+            if report.is_empty() {
+                None
+            } else {
+                Some(report)
+            }
+        }
+    }
 }
 
-fn exit() -> ! {
+fn exit(code: i32) -> ! {
     if atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout) {
         eprint!("Press enter to exit...");
         std::io::Write::flush(&mut std::io::stdout()).unwrap();
         drop(std::io::stdin().read_line(&mut String::new()));
     }
-    std::process::exit(0);
+    std::process::exit(code);
 }
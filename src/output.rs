@@ -0,0 +1,217 @@
+use owo_colors::OwoColorize as _;
+
+use crate::{
+    Diagnostic, DiagnosticLevel, Lint,
+    lints::Category,
+    project::{MapError, ProjectError},
+};
+
+mod sarif;
+
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    JsonLines,
+    Sarif,
+}
+
+/// The diagnostics produced by a single lint on a single map.
+pub struct LintReport {
+    pub lint: &'static dyn Lint,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Diagnostics silenced by `@lint-allow` comments. Only filled in when they were asked for.
+    pub suppressed: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty() && self.suppressed.is_empty()
+    }
+}
+
+pub struct Report {
+    /// The game folder or files in it that kept maps from being found.
+    pub errors: Vec<ProjectError>,
+    /// Diagnostics of project lints, which are only run on whole game folders.
+    pub project: Vec<LintReport>,
+    pub maps: Vec<MapReport>,
+    /// Whether a single map was checked rather than a game folder.
+    pub single: bool,
+}
+
+pub struct MapReport {
+    /// Parsed from the `MapXXXX.lmu` file name, if it follows that convention.
+    pub id: Option<u16>,
+    pub file: String,
+    pub result: Result<Vec<LintReport>, MapError>,
+}
+
+pub fn write(format: &Format, report: &Report) {
+    match format {
+        Format::Text => text(report),
+        Format::Json => json(report),
+        Format::JsonLines => json_lines(report),
+        Format::Sarif => sarif::write(report),
+    }
+}
+
+fn text(report: &Report) {
+    for err in &report.errors {
+        println!("{}\n  {}", err.path().display(), err.red());
+    }
+
+    for map in &report.maps {
+        match &map.result {
+            Ok(lints) if report.single => {
+                for line in lints.iter().flat_map(text_lines) {
+                    println!("{line}");
+                }
+            }
+            Ok(lints) => {
+                println!("{}:", map.file);
+                for line in lints.iter().flat_map(text_lines) {
+                    println!("  {line}");
+                }
+            }
+            Err(MapError::Io(err)) => println!("{}\n  {}", map.file, err.red()),
+            Err(MapError::Invalid(err)) => println!(
+                "{}\n  {}: {}",
+                map.file,
+                "Invalid map file".on_red(),
+                err.red()
+            ),
+        }
+    }
+
+    if !report.project.is_empty() {
+        println!("Project:");
+        for line in report.project.iter().flat_map(text_lines) {
+            println!("  {line}");
+        }
+    }
+}
+
+fn text_lines(report: &LintReport) -> Vec<String> {
+    let LintReport {
+        lint,
+        diagnostics,
+        suppressed,
+    } = report;
+    let number = lint.number();
+    let code = lint.code();
+
+    if report.is_empty() {
+        return vec![format!("L{number:04} {code}: {}", lint.name().green())];
+    }
+
+    let mut items = Vec::with_capacity(diagnostics.len() + 1);
+    items.push(format!("L{number:04} {code}: {}:", lint.name()));
+    items.extend(diagnostics.iter().map(|diagnostic| match diagnostic.level {
+        DiagnosticLevel::Warning => format!("  {}", diagnostic.yellow()),
+        DiagnosticLevel::Error => format!("  {}", diagnostic.red()),
+    }));
+    items.extend(
+        suppressed
+            .iter()
+            .map(|diagnostic| format!("  {}", format!("{diagnostic} (suppressed)").dimmed())),
+    );
+    items
+}
+
+/// A flattened diagnostic, carrying everything needed to locate it without the surrounding report.
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    map: Option<u16>,
+    /// Missing for diagnostics of project lints.
+    file: Option<&'a str>,
+    #[serde(skip)]
+    lint: Option<&'static dyn Lint>,
+    code: Option<&'static str>,
+    number: Option<usize>,
+    category: Option<Category>,
+    name: Option<&'static str>,
+    suppressed: bool,
+    #[serde(flatten)]
+    diagnostic: std::borrow::Cow<'a, Diagnostic>,
+}
+
+fn records(report: &Report) -> impl Iterator<Item = Record<'_>> {
+    let errors = report.errors.iter().map(|err| Record {
+        map: None,
+        file: err.path().to_str(),
+        lint: None,
+        code: None,
+        number: None,
+        category: None,
+        name: None,
+        suppressed: false,
+        diagnostic: std::borrow::Cow::Owned(Diagnostic {
+            level: DiagnosticLevel::Error,
+            event: None,
+            message: Some(err.to_string()),
+        }),
+    });
+    let maps = report.maps.iter().flat_map(|map| match &map.result {
+        Ok(lints) => either::Left(lint_records(lints, map.id, Some(&map.file))),
+        Err(err) => either::Right(std::iter::once(Record {
+            map: map.id,
+            file: Some(&map.file),
+            lint: None,
+            code: None,
+            number: None,
+            category: None,
+            name: None,
+            suppressed: false,
+            diagnostic: std::borrow::Cow::Owned(Diagnostic {
+                level: DiagnosticLevel::Error,
+                event: None,
+                message: Some(err.to_string()),
+            }),
+        })),
+    });
+
+    errors
+        .chain(maps)
+        .chain(lint_records(&report.project, None, None))
+}
+
+fn lint_records<'a>(
+    lints: &'a [LintReport],
+    map: Option<u16>,
+    file: Option<&'a str>,
+) -> impl Iterator<Item = Record<'a>> {
+    lints.iter().flat_map(move |lint| {
+        let diagnostics = lint
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (false, diagnostic));
+        let suppressed = lint.suppressed.iter().map(|diagnostic| (true, diagnostic));
+        diagnostics
+            .chain(suppressed)
+            .map(move |(suppressed, diagnostic)| Record {
+                map,
+                file,
+                lint: Some(lint.lint),
+                code: Some(lint.lint.code()),
+                number: Some(lint.lint.number()),
+                category: Some(lint.lint.category()),
+                name: Some(lint.lint.name()),
+                suppressed,
+                diagnostic: std::borrow::Cow::Borrowed(diagnostic),
+            })
+    })
+}
+
+fn json(report: &Report) {
+    let records = records(report).collect::<Vec<_>>();
+    serde_json::to_writer_pretty(std::io::stdout().lock(), &records).unwrap();
+    println!();
+}
+
+fn json_lines(report: &Report) {
+    for record in records(report) {
+        println!("{}", serde_json::to_string(&record).unwrap());
+    }
+}
@@ -0,0 +1,225 @@
+//! Minimal writer for the subset of [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) used by code-scanning dashboards.
+
+use crate::{DiagnosticLevel, lints, lints::Category};
+
+use super::{Record, Report};
+
+#[derive(serde::Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(serde::Serialize)]
+struct Run {
+    tool: Tool,
+    invocations: [Invocation; 1],
+    results: Vec<SarifResult>,
+}
+
+#[derive(serde::Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(serde::Serialize)]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    name: &'static str,
+    short_description: Message,
+    default_configuration: Configuration,
+    properties: RuleProperties,
+}
+
+#[derive(serde::Serialize)]
+struct RuleProperties {
+    category: Category,
+    /// The permanent `L0001` style alias of the rule.
+    alias: String,
+}
+
+#[derive(serde::Serialize)]
+struct Configuration {
+    level: &'static str,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<Notification>,
+}
+
+#[derive(serde::Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+    locations: [Location; 1],
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: [Location; 1],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suppressions: Vec<Suppression>,
+}
+
+#[derive(serde::Serialize)]
+struct Suppression {
+    kind: &'static str,
+}
+
+#[derive(serde::Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logical_locations: Vec<LogicalLocation>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+}
+
+#[derive(serde::Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogicalLocation {
+    fully_qualified_name: String,
+    kind: &'static str,
+}
+
+const fn level(level: DiagnosticLevel) -> &'static str {
+    match level {
+        DiagnosticLevel::Warning => "warning",
+        DiagnosticLevel::Error => "error",
+    }
+}
+
+fn location(record: &Record) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                // project lints are attributed to the file that defines the project
+                uri: record.file.unwrap_or("RPG_RT.lmt").to_string(),
+            },
+        },
+        logical_locations: record
+            .diagnostic
+            .event
+            .as_ref()
+            .map(|event| LogicalLocation {
+                fully_qualified_name: event.logical_location(),
+                kind: "object",
+            })
+            .into_iter()
+            .collect(),
+    }
+}
+
+pub fn write(report: &Report) {
+    let rules = lints::ALL
+        .iter()
+        .map(|lint| Rule {
+            id: lint.code().to_string(),
+            name: lint.name(),
+            short_description: Message {
+                text: lint.name().to_string(),
+            },
+            default_configuration: Configuration {
+                level: level(lint.default_level()),
+            },
+            properties: RuleProperties {
+                category: lint.category(),
+                alias: format!("L{:04}", lint.number()),
+            },
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    let mut notifications = Vec::new();
+    for record in super::records(report) {
+        let Some(lint) = record.lint else {
+            notifications.push(Notification {
+                level: level(record.diagnostic.level),
+                message: Message {
+                    text: record.diagnostic.to_string(),
+                },
+                locations: [location(&record)],
+            });
+            continue;
+        };
+
+        results.push(SarifResult {
+            rule_id: lint.code().to_string(),
+            rule_index: lints::ALL
+                .iter()
+                .position(|rule| rule.code() == lint.code())
+                .unwrap(),
+            level: level(record.diagnostic.level),
+            message: Message {
+                text: record
+                    .diagnostic
+                    .message
+                    .clone()
+                    .or_else(|| record.name.map(str::to_string))
+                    .unwrap_or_default(),
+            },
+            locations: [location(&record)],
+            suppressions: if record.suppressed {
+                vec![Suppression { kind: "inSource" }]
+            } else {
+                Vec::new()
+            },
+        });
+    }
+
+    let log = Log {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules,
+                },
+            },
+            invocations: [Invocation {
+                // files that could not be read leave the run incomplete
+                execution_successful: notifications.is_empty(),
+                tool_execution_notifications: notifications,
+            }],
+            results,
+        }],
+    };
+
+    serde_json::to_writer_pretty(std::io::stdout().lock(), &log).unwrap();
+    println!();
+}
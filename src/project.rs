@@ -0,0 +1,596 @@
+//! The game folder as a whole, shared by every lint.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use lcf::{
+    ConvertExt,
+    helpers::{Array2D, Number, UnknownChunk},
+    raw::{
+        ldb::{LcfDataBaseChunk, RawLcfDataBase, switch::SwitchChunk, variable::VariableChunk},
+        lmt::{RawLcfMapTree, map::MapChunk},
+        lmu::event::instruction::Instruction,
+    },
+};
+
+pub struct Project {
+    pub dir: std::path::PathBuf,
+    /// The `MapXXXX.lmu` files in `dir` by id, whatever the case of their names.
+    pub map_files: BTreeMap<u16, std::path::PathBuf>,
+    /// Missing when a single map is checked outside of a game folder.
+    pub tree: Option<lcf::lmt::LcfMapTree>,
+    /// Maps listed more than once in the tree, of which only the first entry was kept.
+    pub duplicate_maps: Vec<u16>,
+    /// Missing when there is no `RPG_RT.ldb` or it could not be read, which is reported as well.
+    pub database: Option<lcf::ldb::LcfDataBase>,
+    /// Taken from the database as well, empty along with it.
+    pub names: Names,
+    /// One slot for every map in the tree, filled in the first time the map is read.
+    summaries: HashMap<u16, OnceLock<Result<MapSummary, MapError>>>,
+    graph: OnceLock<crate::graph::Graph>,
+    files: OnceLock<crate::assets::Files>,
+}
+
+/// What lints checking other maps need to know about a map, kept for the whole run.
+pub struct MapSummary {
+    pub width: u32,
+    pub height: u32,
+    pub chipset: u32,
+    pub lower: Vec<u16>,
+    pub upper: Vec<u16>,
+    pub events: Vec<EventSummary>,
+    /// Every transfer of the player to a fixed destination, in event order.
+    pub transfers: Vec<Transfer>,
+    /// Common events called by any event of the map.
+    pub common_events: Vec<u32>,
+    /// Files the map refers to, the chipset aside.
+    pub assets: Vec<crate::assets::Asset>,
+}
+
+/// Names of the database entries that the decoded database leaves out, by id.
+#[derive(Default)]
+pub struct Names {
+    pub items: BTreeMap<u32, String>,
+    pub skills: BTreeMap<u32, String>,
+    pub switches: BTreeMap<u32, String>,
+    pub variables: BTreeMap<u32, String>,
+}
+
+impl Names {
+    fn new(raw: &RawLcfDataBase) -> Self {
+        let mut names = Self::default();
+        for chunk in &raw.0.inner_vec {
+            match &chunk.data {
+                LcfDataBaseChunk::Items(bytes) => names.items = decode_names(bytes),
+                LcfDataBaseChunk::Skills(bytes) => names.skills = decode_names(bytes),
+                LcfDataBaseChunk::Switches(table) => {
+                    names.switches = names_of(table.inner_vec.iter().map(|(id, fields)| {
+                        let name = fields.inner_vec.iter().find_map(|field| match &field.data {
+                            SwitchChunk::Name(name) => Some(name.as_slice()),
+                            SwitchChunk::Unknown { .. } => None,
+                        });
+                        (id.0, name)
+                    }));
+                }
+                LcfDataBaseChunk::Variables(table) => {
+                    names.variables = names_of(table.inner_vec.iter().map(|(id, fields)| {
+                        let name = fields.inner_vec.iter().find_map(|field| match &field.data {
+                            VariableChunk::Name(name) => Some(name.as_slice()),
+                            VariableChunk::Unknown { .. } => None,
+                        });
+                        (id.0, name)
+                    }));
+                }
+                _ => (),
+            }
+        }
+        names
+    }
+}
+
+/// Reads the names of a table the decoded database only has as bytes, which is the first field of
+/// every entry. Tables that cannot be made sense of are left empty.
+fn decode_names(bytes: &[u8]) -> BTreeMap<u32, String> {
+    let Ok(table) =
+        <Array2D<UnknownChunk> as binrw::BinRead>::read(&mut std::io::Cursor::new(bytes))
+    else {
+        return BTreeMap::new();
+    };
+
+    names_of(table.inner_vec.iter().map(|(id, fields)| {
+        let name = fields.inner_vec.iter().find_map(|field| match &field.data {
+            UnknownChunk::Unknown { id: 1, bytes } => Some(bytes.as_slice()),
+            UnknownChunk::Unknown { .. } => None,
+        });
+        (id.0, name)
+    }))
+}
+
+/// Decodes the names of every entry, leaving the entries without one blank.
+fn names_of<'a>(entries: impl Iterator<Item = (u32, Option<&'a [u8]>)>) -> BTreeMap<u32, String> {
+    entries
+        .map(|(id, name)| {
+            let name = name
+                .map(|name| encoding_rs::SHIFT_JIS.decode(name).0.into_owned())
+                .unwrap_or_default();
+            (id, name)
+        })
+        .collect()
+}
+
+pub struct Transfer {
+    /// The event doing the transfer.
+    pub event: u32,
+    pub map: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+pub struct EventSummary {
+    pub id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub pages: Vec<PageSummary>,
+}
+
+pub struct PageSummary {
+    pub priority: lcf::enums::Priority,
+    /// The upper layer tile shown instead of a character, if any.
+    pub tile: Option<u32>,
+}
+
+impl MapSummary {
+    #[must_use]
+    pub fn new(map: &lcf::lmu::LcfMapUnit) -> Self {
+        let mut transfers = Vec::new();
+        let mut common_events = Vec::new();
+        for event in &map.events {
+            for command in event.pages.iter().flat_map(|page| &page.commands) {
+                match command.instruction {
+                    Instruction::TransferPlayer { map, x, y, .. } => transfers.push(Transfer {
+                        event: event.id,
+                        map,
+                        x,
+                        y,
+                    }),
+                    Instruction::CallEvent { mode: 0, index, .. } => common_events.push(index),
+                    _ => (),
+                }
+            }
+        }
+
+        Self {
+            width: map.width,
+            height: map.height,
+            chipset: map.chipset,
+            lower: map.lower.clone(),
+            upper: map.upper.clone(),
+            events: map
+                .events
+                .iter()
+                .map(|event| EventSummary {
+                    id: event.id,
+                    x: event.x,
+                    y: event.y,
+                    pages: event
+                        .pages
+                        .iter()
+                        .map(|page| PageSummary {
+                            priority: page.priority,
+                            tile: page.graphic.file.is_empty().then_some(page.graphic.index),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            transfers,
+            common_events,
+            assets: crate::assets::of_map(map),
+        }
+    }
+
+    #[must_use]
+    pub const fn contains(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The lower and upper layer tiles at a position, `None` outside of the map.
+    #[must_use]
+    pub fn tiles(&self, x: u32, y: u32) -> Option<(u16, u16)> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let index = usize::try_from(y.checked_mul(self.width)?.checked_add(x)?).ok()?;
+        Some((*self.lower.get(index)?, *self.upper.get(index)?))
+    }
+}
+
+pub enum MapError {
+    Io(std::io::Error),
+    Invalid(lcf::lmu::LcfMapUnitReadError),
+}
+
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Invalid(err) => write!(f, "Invalid map file: {err}"),
+        }
+    }
+}
+
+/// Why a game folder, or the part of it needed to find its maps, could not be read.
+pub enum ProjectError {
+    NotFound(std::path::PathBuf),
+    Io(std::path::PathBuf, std::io::Error),
+    /// Not made by RPG Maker 2000 or 2003.
+    NotLcf(std::path::PathBuf),
+    /// Damaged, at the byte offset if it is known.
+    Corrupt {
+        path: std::path::PathBuf,
+        offset: Option<u64>,
+        reason: String,
+    },
+}
+
+impl ProjectError {
+    #[must_use]
+    pub fn io(path: std::path::PathBuf, err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            Self::NotFound(path)
+        } else {
+            Self::Io(path, err)
+        }
+    }
+
+    fn decode(path: std::path::PathBuf, err: &binrw::Error) -> Self {
+        let (offset, reason) = match err.root_cause() {
+            binrw::Error::BadMagic { .. } => return Self::NotLcf(path),
+            err if err.is_eof() => (None, "the file ends too early".to_string()),
+            binrw::Error::AssertFail { pos, message } => (Some(*pos), message.clone()),
+            binrw::Error::Custom { pos, err } => (Some(*pos), err.to_string()),
+            binrw::Error::NoVariantMatch { pos } | binrw::Error::EnumErrors { pos, .. } => {
+                (Some(*pos), "unknown data".to_string())
+            }
+            err => (None, err.to_string()),
+        };
+        Self::Corrupt {
+            path,
+            offset,
+            reason,
+        }
+    }
+
+    /// The file or folder that could not be read.
+    #[must_use]
+    pub fn path(&self) -> &std::path::Path {
+        match self {
+            Self::NotFound(path)
+            | Self::Io(path, _)
+            | Self::NotLcf(path)
+            | Self::Corrupt { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(_) => write!(f, "File not found"),
+            Self::Io(_, err) => write!(f, "{err}"),
+            Self::NotLcf(_) => write!(f, "Not an RPG Maker 2000/2003 project"),
+            Self::Corrupt {
+                offset: Some(offset),
+                reason,
+                ..
+            } => write!(f, "Corrupt file at byte {offset:#x}: {reason}"),
+            Self::Corrupt { reason, .. } => write!(f, "Corrupt file: {reason}"),
+        }
+    }
+}
+
+impl Project {
+    /// The project with its maps in `dir`, from the parts of it that could be read.
+    #[must_use]
+    pub fn new(dir: std::path::PathBuf, database: Option<Database>, tree: Option<MapTree>) -> Self {
+        let (tree, duplicate_maps) = tree
+            .map(|MapTree { tree, duplicates }| (tree, duplicates))
+            .unzip();
+        let (database, names) = database
+            .map(|Database { database, names }| (database, names))
+            .unzip();
+        // areas and the root have no map file of their own
+        let summaries = tree
+            .iter()
+            .flat_map(|tree| &tree.maps)
+            .filter(|(_, map)| matches!(map.r#type, lcf::lmt::MapType::Map))
+            .map(|(id, _)| (*id, OnceLock::new()))
+            .collect();
+
+        Self {
+            map_files: map_files(&dir),
+            dir,
+            tree,
+            duplicate_maps: duplicate_maps.unwrap_or_default(),
+            database,
+            names: names.unwrap_or_default(),
+            summaries,
+            graph: OnceLock::new(),
+            files: OnceLock::new(),
+        }
+    }
+
+    /// The file of a map, which is where it would be expected if there is none.
+    #[must_use]
+    pub fn map_path(&self, id: u16) -> std::path::PathBuf {
+        self.map_files
+            .get(&id)
+            .cloned()
+            .unwrap_or_else(|| self.dir.join(format!("Map{id:04}.lmu")))
+    }
+
+    /// Summary of a map in the tree, reading the map if nothing did so yet.
+    ///
+    /// `None` if the tree has no such map, which includes areas.
+    #[must_use]
+    pub fn summary(&self, id: u16) -> Option<&Result<MapSummary, MapError>> {
+        let slot = self.summaries.get(&id)?;
+        Some(slot.get_or_init(|| read_map(&self.map_path(id)).map(|map| MapSummary::new(&map))))
+    }
+
+    /// The transfers between all maps, read from every map that nothing read yet.
+    pub fn graph(&self) -> &crate::graph::Graph {
+        self.graph.get_or_init(|| crate::graph::Graph::new(self))
+    }
+
+    /// The asset files of the game folder and the RTP, listed the first time they are needed.
+    ///
+    /// The RTP folders of the first call are the ones used for the whole run.
+    pub fn files(&self, rtp: &[std::path::PathBuf]) -> &crate::assets::Files {
+        self.files
+            .get_or_init(|| crate::assets::Files::new(&self.dir, rtp))
+    }
+
+    /// Records the summary of a map that was already read, so that it does not have to be read again.
+    pub fn remember(&self, id: u16, map: &lcf::lmu::LcfMapUnit) {
+        if let Some(slot) = self.summaries.get(&id) {
+            slot.get_or_init(|| Ok(MapSummary::new(map)));
+        }
+    }
+}
+
+/// Opens the game at `path`, recording the parts of it that could not be read.
+///
+/// A map file is returned to be checked on its own, and any other file stands for the folder it is
+/// in. `tree` and `database` are used instead of the files of the folder. Fails only if `path`
+/// itself cannot be read.
+pub fn open(
+    path: &std::path::Path,
+    tree: Option<&std::path::Path>,
+    database: Option<&std::path::Path>,
+    errors: &mut Vec<ProjectError>,
+) -> Result<(Option<std::path::PathBuf>, Project), ProjectError> {
+    let metadata = std::fs::metadata(path).map_err(|err| ProjectError::io(path.to_owned(), err))?;
+    let (single, dir) = if metadata.is_dir() {
+        (None, path.to_owned())
+    } else {
+        let map = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("lmu"));
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| std::path::Path::new("."));
+        (map.then(|| path.to_owned()), parent.to_owned())
+    };
+
+    let tree_path = tree.map_or_else(|| find_file(&dir, "RPG_RT.lmt"), std::path::Path::to_owned);
+    let tree = match read_tree(&tree_path) {
+        Ok(tree) => Some(tree),
+        // a single map does not need a tree, unless one was asked for
+        Err(ProjectError::NotFound(_)) if single.is_some() && tree.is_none() => None,
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    };
+    let database_path =
+        database.map_or_else(|| find_file(&dir, "RPG_RT.ldb"), std::path::Path::to_owned);
+    let database = match read_database(&database_path) {
+        // the database is optional, unless one was asked for
+        Ok(None) if database.is_some() => {
+            errors.push(ProjectError::NotFound(database_path));
+            None
+        }
+        Ok(database) => database,
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    };
+
+    Ok((single, Project::new(dir, database, tree)))
+}
+
+pub fn read_map(path: &std::path::Path) -> Result<lcf::lmu::LcfMapUnit, MapError> {
+    let bytes = std::fs::read(path).map_err(MapError::Io)?;
+    let mut cursor = std::io::Cursor::new(bytes);
+    lcf::lmu::LcfMapUnit::read(&mut cursor).map_err(MapError::Invalid)
+}
+
+/// The file with the name in `dir`, whatever its case. The exact name wins if there are several,
+/// and is also what is returned if there are none.
+#[must_use]
+pub fn find_file(dir: &std::path::Path, name: &str) -> std::path::PathBuf {
+    let exact = dir.join(name);
+    if exact.is_file() {
+        return exact;
+    }
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map_or(exact, |entry| entry.path())
+}
+
+/// The id of the map in a file named `MapXXXX.lmu`, in any case.
+#[must_use]
+pub fn map_id(file: &str) -> Option<u16> {
+    file.get(3..7)
+        .and_then(|digits| digits.parse().ok())
+        .filter(|id| file.eq_ignore_ascii_case(&format!("Map{id:04}.lmu")))
+}
+
+/// Lists the map files in `dir`, preferring the exact `MapXXXX.lmu` spelling when the same map
+/// is there in several cases.
+fn map_files(dir: &std::path::Path) -> BTreeMap<u16, std::path::PathBuf> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let file = entry.file_name();
+        let Some(file) = file.to_str() else {
+            continue;
+        };
+        let Some(id) = map_id(file) else {
+            continue;
+        };
+        if file == format!("Map{id:04}.lmu") || !files.contains_key(&id) {
+            files.insert(id, entry.path());
+        }
+    }
+    files
+}
+
+/// The map tree, along with what had to be left out to read it.
+pub struct MapTree {
+    pub tree: lcf::lmt::LcfMapTree,
+    /// Maps listed more than once, of which only the first entry is kept.
+    pub duplicates: Vec<u16>,
+}
+
+/// Reads the map tree at `path`.
+pub fn read_tree(path: &std::path::Path) -> Result<MapTree, ProjectError> {
+    let path = path.to_owned();
+    let bytes = std::fs::read(&path).map_err(|err| ProjectError::io(path.clone(), err))?;
+    let mut raw = <RawLcfMapTree as binrw::BinRead>::read(&mut std::io::Cursor::new(bytes))
+        .map_err(|err| ProjectError::decode(path.clone(), &err))?;
+
+    // lcf refuses trees listing a map twice, which the editor opens just fine
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    raw.maps.inner_vec.retain(|(id, _)| {
+        seen.insert(id.0) || {
+            duplicates.extend(u16::try_from(id.0));
+            false
+        }
+    });
+    let mut seen = HashSet::new();
+    raw.order.retain(|id| seen.insert(id.0));
+    duplicates.sort_unstable();
+    duplicates.dedup();
+
+    // lcf asserts this rather than failing
+    if raw.maps.inner_vec.len() != raw.order.len() {
+        return Err(ProjectError::Corrupt {
+            path,
+            offset: None,
+            reason: "the map order does not list every map".to_string(),
+        });
+    }
+    // and unwraps the conversion of each map
+    for (id, chunks) in &raw.maps.inner_vec {
+        lcf::lmt::Map::from_chunks(id.0, chunks.inner_vec.clone()).map_err(|err| {
+            ProjectError::Corrupt {
+                path: path.clone(),
+                offset: None,
+                reason: err.to_string(),
+            }
+        })?;
+    }
+    // lcf reads areas starting at the top left corner as maps
+    let areas = raw
+        .maps
+        .inner_vec
+        .iter()
+        .filter(|(_, chunks)| {
+            chunks
+                .inner_vec
+                .iter()
+                .any(|chunk| matches!(chunk.data, MapChunk::Type(Number(2))))
+        })
+        .filter_map(|(id, chunks)| {
+            let area = chunks.inner_vec.iter().find_map(|chunk| match chunk.data {
+                MapChunk::AreaRange {
+                    begin_x,
+                    begin_y,
+                    end_x,
+                    end_y,
+                } => Some(lcf::lmt::MapType::Area {
+                    begin_x,
+                    begin_y,
+                    end_x,
+                    end_y,
+                }),
+                _ => None,
+            });
+            Some((u16::try_from(id.0).ok()?, area?))
+        })
+        .collect::<Vec<_>>();
+
+    let mut tree = lcf::lmt::LcfMapTree::try_from(raw).map_err(|err| ProjectError::Corrupt {
+        path,
+        offset: None,
+        reason: err.to_string(),
+    })?;
+    for (id, area) in areas {
+        if let Some(map) = tree.maps.get_mut(&id) {
+            map.r#type = area;
+        }
+    }
+    Ok(MapTree { tree, duplicates })
+}
+
+/// The database, along with the names it keeps as raw bytes.
+pub struct Database {
+    pub database: lcf::ldb::LcfDataBase,
+    pub names: Names,
+}
+
+/// Reads the database at `path`, which is `None` if there is no such file.
+pub fn read_database(path: &std::path::Path) -> Result<Option<Database>, ProjectError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(ProjectError::io(path.to_owned(), err)),
+    };
+    let raw = <RawLcfDataBase as binrw::BinRead>::read(&mut std::io::Cursor::new(bytes))
+        .map_err(|err| ProjectError::decode(path.to_owned(), &err))?;
+    let names = Names::new(&raw);
+    let database = lcf::ldb::LcfDataBase::try_from(raw).map_err(|err| match err {
+        lcf::ldb::LcfDataBaseReadError::Decode(err) => ProjectError::decode(path.to_owned(), &err),
+        err => ProjectError::Corrupt {
+            path: path.to_owned(),
+            offset: None,
+            reason: err.to_string(),
+        },
+    })?;
+    Ok(Some(Database { database, names }))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn map_id() {
+        assert_eq!(super::map_id("Map0012.lmu"), Some(12));
+        assert_eq!(super::map_id("MAP0012.LMU"), Some(12));
+        assert_eq!(super::map_id("map12.lmu"), None);
+        assert_eq!(super::map_id("Map0012.lmu.bak"), None);
+        assert_eq!(super::map_id("Map00120.lmu"), None);
+    }
+}
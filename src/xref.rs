@@ -0,0 +1,468 @@
+//! Where every switch and variable is read or written, across all maps and common events.
+//!
+//! Only ids that are known without running the game are listed. Switches and variables picked
+//! through another variable count as a read of that other variable.
+
+use std::io::Write;
+
+use lcf::raw::lmu::event::{command::Command, instruction::Instruction};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::project::Project;
+
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The game folder, or any file in it.
+    #[arg(index = 1, default_value = ".")]
+    path: std::path::PathBuf,
+    /// Map tree to use instead of the `RPG_RT.lmt` in the game folder.
+    #[arg(long)]
+    tree: Option<std::path::PathBuf>,
+    /// Database to use instead of the `RPG_RT.ldb` in the game folder.
+    #[arg(long)]
+    database: Option<std::path::PathBuf>,
+    /// Only list these switches.
+    #[arg(long, value_delimiter = ',')]
+    switch: Vec<u32>,
+    /// Only list these variables.
+    #[arg(long, value_delimiter = ',')]
+    variable: Vec<u32>,
+    #[arg(long, default_value = "text")]
+    format: Format,
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Switch,
+    Variable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "kebab-case", untagged)]
+pub enum Location {
+    Map {
+        map: u16,
+        event: u32,
+        page: usize,
+        /// Missing for the conditions of the page.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<usize>,
+    },
+    CommonEvent {
+        common_event: u32,
+        /// Missing for the condition of the common event.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        command: Option<usize>,
+    },
+}
+
+impl Kind {
+    /// Formats an id the way the editor shows it, as in `S0001` or `V0044`.
+    #[must_use]
+    pub fn name(self, id: u32) -> String {
+        match self {
+            Self::Switch => format!("S{id:04}"),
+            Self::Variable => format!("V{id:04}"),
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let command = match *self {
+            Self::Map {
+                map,
+                event,
+                page,
+                command,
+            } => {
+                write!(f, "Map{map:04}/EV{event:04}/P{page:02}")?;
+                command
+            }
+            Self::CommonEvent {
+                common_event,
+                command,
+            } => {
+                write!(f, "CEV{common_event:04}")?;
+                command
+            }
+        };
+        match command {
+            Some(command) => write!(f, "/I{command:05}"),
+            None => write!(f, "/condition"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct Reference {
+    pub kind: Kind,
+    pub id: u32,
+    pub access: Access,
+    #[serde(flatten)]
+    pub location: Location,
+}
+
+/// Lists the references of the game at `args.path`. A map file stands for its folder here, since
+/// the references of common events are shared by every map.
+pub fn run(args: &Args) -> std::io::Result<()> {
+    let error = |err: crate::project::ProjectError| {
+        std::io::Error::other(format!("{}: {err}", err.path().display()))
+    };
+    let mut errors = Vec::new();
+    let (_, project) = crate::project::open(
+        &args.path,
+        args.tree.as_deref(),
+        args.database.as_deref(),
+        &mut errors,
+    )
+    .map_err(error)?;
+    // a partial list would pass for a complete one
+    if let Some(err) = errors.into_iter().next() {
+        return Err(error(err));
+    }
+
+    let mut references = references(&project);
+    if !args.switch.is_empty() || !args.variable.is_empty() {
+        references.retain(|reference| match reference.kind {
+            Kind::Switch => args.switch.contains(&reference.id),
+            Kind::Variable => args.variable.contains(&reference.id),
+        });
+    }
+
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    match args.format {
+        Format::Text => text(&mut out, &project, &references)?,
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &entries(&project, &references))?;
+            writeln!(out)?;
+        }
+        Format::Csv => csv(&mut out, &project, &references)?,
+    }
+    out.flush()
+}
+
+/// The references to one switch or variable.
+#[derive(serde::Serialize)]
+struct Entry<'a> {
+    kind: Kind,
+    id: u32,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    name: &'a str,
+    references: Vec<Use>,
+}
+
+#[derive(serde::Serialize)]
+struct Use {
+    access: Access,
+    #[serde(flatten)]
+    location: Location,
+}
+
+fn entries<'a>(project: &'a Project, references: &[Reference]) -> Vec<Entry<'a>> {
+    references
+        .chunk_by(|a, b| (a.kind, a.id) == (b.kind, b.id))
+        .map(|group| Entry {
+            kind: group[0].kind,
+            id: group[0].id,
+            name: name(project, group[0].kind, group[0].id),
+            references: group
+                .iter()
+                .map(|reference| Use {
+                    access: reference.access,
+                    location: reference.location,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn name(project: &Project, kind: Kind, id: u32) -> &str {
+    let names = match kind {
+        Kind::Switch => &project.names.switches,
+        Kind::Variable => &project.names.variables,
+    };
+    names.get(&id).map_or("", String::as_str)
+}
+
+fn text(out: &mut impl Write, project: &Project, references: &[Reference]) -> std::io::Result<()> {
+    for entry in entries(project, references) {
+        if entry.name.is_empty() {
+            writeln!(out, "{}", entry.kind.name(entry.id))?;
+        } else {
+            writeln!(out, "{} {}", entry.kind.name(entry.id), entry.name)?;
+        }
+        for reference in entry.references {
+            let access = match reference.access {
+                Access::Read => "read ",
+                Access::Write => "write",
+            };
+            writeln!(out, "  {access} {}", reference.location)?;
+        }
+    }
+    Ok(())
+}
+
+fn csv(out: &mut impl Write, project: &Project, references: &[Reference]) -> std::io::Result<()> {
+    writeln!(out, "kind,id,name,access,location")?;
+    for reference in references {
+        let kind = match reference.kind {
+            Kind::Switch => "switch",
+            Kind::Variable => "variable",
+        };
+        let access = match reference.access {
+            Access::Read => "read",
+            Access::Write => "write",
+        };
+        let name = name(project, reference.kind, reference.id).replace('"', "\"\"");
+        writeln!(
+            out,
+            "{kind},{},\"{name}\",{access},{}",
+            reference.id, reference.location
+        )?;
+    }
+    Ok(())
+}
+
+/// Every reference in the project, sorted by switch or variable.
+#[must_use]
+pub fn references(project: &Project) -> Vec<Reference> {
+    // without a tree, which a single map does not need, every map file of the folder is read
+    let maps = project.tree.as_ref().map_or_else(
+        || project.map_files.keys().copied().collect::<Vec<_>>(),
+        |tree| {
+            tree.maps
+                .iter()
+                .filter(|(_, map)| matches!(map.r#type, lcf::lmt::MapType::Map))
+                .map(|(id, _)| *id)
+                .collect()
+        },
+    );
+
+    // unreadable maps are reported when linting
+    let mut references = maps
+        .into_par_iter()
+        .flat_map_iter(|id| {
+            let mut references = Vec::new();
+            if let Ok(map) = crate::project::read_map(&project.map_path(id)) {
+                for event in &map.events {
+                    for (page_index, page) in event.pages.iter().enumerate() {
+                        let location = |command| Location::Map {
+                            map: id,
+                            event: event.id,
+                            page: page_index + 1,
+                            command,
+                        };
+
+                        let condition = &page.condition;
+                        for (enabled, switch) in [condition.switch_a, condition.switch_b] {
+                            if enabled {
+                                references.push((
+                                    Kind::Switch,
+                                    switch,
+                                    Access::Read,
+                                    location(None),
+                                ));
+                            }
+                        }
+                        if condition.variable.0 {
+                            references.push((
+                                Kind::Variable,
+                                condition.variable.1,
+                                Access::Read,
+                                location(None),
+                            ));
+                        }
+
+                        commands(&page.commands, |index, kind, id, access| {
+                            references.push((kind, id, access, location(Some(index + 1))));
+                        });
+                    }
+                }
+            }
+            references
+        })
+        .collect::<Vec<_>>();
+
+    for common_event in project
+        .database
+        .iter()
+        .flat_map(|database| &database.common_events)
+    {
+        let location = |command| Location::CommonEvent {
+            common_event: common_event.id,
+            command,
+        };
+        if common_event.state {
+            references.push((
+                Kind::Switch,
+                common_event.switch,
+                Access::Read,
+                location(None),
+            ));
+        }
+        commands(&common_event.commands, |index, kind, id, access| {
+            references.push((kind, id, access, location(Some(index + 1))));
+        });
+    }
+
+    let mut references = references
+        .into_iter()
+        .map(|(kind, id, access, location)| Reference {
+            kind,
+            id,
+            access,
+            location,
+        })
+        .collect::<Vec<_>>();
+    references.sort();
+    references.dedup();
+    references
+}
+
+/// Calls `found` with the index of the command for every switch and variable the commands use.
+pub fn commands(commands: &[Command], mut found: impl FnMut(usize, Kind, u32, Access)) {
+    for (index, command) in commands.iter().enumerate() {
+        let mut found = |kind, id, access| found(index, kind, id, access);
+        match command.instruction {
+            Instruction::ControlSwitches {
+                mode, start, end, ..
+            } => match mode {
+                0 => found(Kind::Switch, start, Access::Write),
+                1 => (start..=end).for_each(|id| found(Kind::Switch, id, Access::Write)),
+                _ => found(Kind::Variable, start, Access::Read),
+            },
+            Instruction::ControlVariables {
+                mode,
+                start,
+                end,
+                operand,
+                value1,
+                ..
+            } => {
+                match mode {
+                    0 => found(Kind::Variable, start, Access::Write),
+                    1 => (start..=end).for_each(|id| found(Kind::Variable, id, Access::Write)),
+                    _ => found(Kind::Variable, start, Access::Read),
+                }
+                // a variable, or the variable whose id is in a variable
+                if operand == 1 || operand == 2 {
+                    found(Kind::Variable, value1, Access::Read);
+                }
+            }
+            Instruction::ConditionalBranch {
+                mode,
+                field1,
+                field2,
+                field3,
+                ..
+            } => match mode {
+                0 => found(Kind::Switch, field1, Access::Read),
+                1 => {
+                    found(Kind::Variable, field1, Access::Read);
+                    if field2 == 1 {
+                        found(Kind::Variable, field3, Access::Read);
+                    }
+                }
+                _ => (),
+            },
+            Instruction::InputNumber { variable, .. } => {
+                found(Kind::Variable, variable, Access::Write);
+            }
+            Instruction::KeyInputProcessing { ref args } => {
+                if let Some(variable) = args.first() {
+                    found(Kind::Variable, *variable, Access::Write);
+                }
+            }
+            ref instruction => positions(instruction, &mut found),
+        }
+    }
+}
+
+/// The variables used for positions and for picking events.
+fn positions(instruction: &Instruction, found: &mut impl FnMut(Kind, u32, Access)) {
+    match *instruction {
+        Instruction::GetPlayerLocation {
+            output_map,
+            output_x,
+            output_y,
+        } => {
+            for variable in [output_map, output_x, output_y] {
+                found(Kind::Variable, variable, Access::Write);
+            }
+        }
+        Instruction::GetTerrainID {
+            operand,
+            x,
+            y,
+            output,
+        }
+        | Instruction::GetEventID {
+            mode: operand,
+            x,
+            y,
+            output,
+        } => {
+            if operand == 1 {
+                found(Kind::Variable, x, Access::Read);
+                found(Kind::Variable, y, Access::Read);
+            }
+            found(Kind::Variable, output, Access::Write);
+        }
+        Instruction::MoveToVariableLocation {
+            map_variable,
+            x_variable,
+            y_variable,
+        } => {
+            for variable in [map_variable, x_variable, y_variable] {
+                found(Kind::Variable, variable, Access::Read);
+            }
+        }
+        Instruction::SetVehicleLocation {
+            operand: 1,
+            map,
+            x,
+            y,
+            ..
+        } => {
+            for variable in [map, x, y] {
+                found(Kind::Variable, variable, Access::Read);
+            }
+        }
+        Instruction::SetEventLocation {
+            mode: 1,
+            x_pos,
+            y_pos,
+            ..
+        } => {
+            found(Kind::Variable, x_pos, Access::Read);
+            found(Kind::Variable, y_pos, Access::Read);
+        }
+        Instruction::CallEvent {
+            mode: 2,
+            index,
+            page,
+        } => {
+            found(Kind::Variable, index, Access::Read);
+            found(Kind::Variable, page, Access::Read);
+        }
+        _ => (),
+    }
+}